@@ -1,9 +1,197 @@
-use {Client, CatapultResult};
-use client::{JsonResponse};
-use self::info::AccountInfo;
+use {Client, CatapultResult, CatapultError};
+use client::{JsonResponse, RawJsonResponse};
+use self::info::{AccountInfo, TransactionInfo};
 use std::sync::{Mutex, Arc};
+use std::collections::BTreeMap;
 use lazy::Lazy;
 use lazy::Lazy::*;
+use rustc_serialize::json::{Json, ToJson};
+use util;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionType{
+	///Usage, such as a call, message or number
+	Charge,
+	Payment,
+	Credit,
+	///A payment made automatically when the balance ran low
+	AutoRecharge
+}
+impl TransactionType{
+	pub fn parse(transaction_type: &str) -> CatapultResult<TransactionType>{
+		Ok(match transaction_type{
+			"charge" => TransactionType::Charge,
+			"payment" => TransactionType::Payment,
+			"credit" => TransactionType::Credit,
+			"auto-recharge" => TransactionType::AutoRecharge,
+			other @ _ => return Err(CatapultError::unexpected(
+				&format!("unknown Transaction type: {}", other)
+			))
+		})
+	}
+	pub fn to_string(&self) -> &'static str{
+		use self::TransactionType::*;
+		match *self{
+			Charge => "charge",
+			Payment => "payment",
+			Credit => "credit",
+			AutoRecharge => "auto-recharge"
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Transaction{
+	id: String,
+	time: String,
+	amount: f64,
+	transaction_type: TransactionType,
+	units: Option<u64>,
+	product_type: Option<String>,
+	number: Option<String>
+}
+impl Transaction{
+	fn from_info(info: TransactionInfo) -> CatapultResult<Transaction>{
+		Ok(Transaction{
+			amount: try!(info.amount.parse().map_err(|_|{
+				CatapultError::unexpected(&format!("invalid Transaction amount: {}", info.amount))
+			})),
+			transaction_type: try!(TransactionType::parse(&info.transactionType)),
+			id: info.id,
+			time: info.time,
+			units: info.units,
+			product_type: info.productType,
+			number: info.number
+		})
+	}
+	
+	/* Getters */
+	pub fn get_id(&self) -> String{
+		self.id.clone()
+	}
+	pub fn get_time(&self) -> String{
+		self.time.clone()
+	}
+	///In dollars
+	pub fn get_amount(&self) -> f64{
+		self.amount
+	}
+	pub fn get_type(&self) -> TransactionType{
+		self.transaction_type.clone()
+	}
+	///eg: minutes or messages charged for
+	pub fn get_units(&self) -> Option<u64>{
+		self.units
+	}
+	///What was charged for, eg: "local-inbound-call" or "sms-out"
+	pub fn get_product_type(&self) -> Option<String>{
+		self.product_type.clone()
+	}
+	///The phone number involved, if any
+	pub fn get_number(&self) -> Option<String>{
+		self.number.clone()
+	}
+}
+
+fn get_transaction_list<P: ToJson>(client: &Client, path: &str, params: P, min_amount: Option<f64>, max_amount: Option<f64>) -> CatapultResult<QueryResult>{
+	let res:RawJsonResponse = try!(client.raw_get_request(&path, params, ()));
+	let list = match res.body{
+		Json::Array(list) => list,
+		_ => return Err(CatapultError::unexpected("expected a list of transactions"))
+	};
+	let mut output = vec!();
+	for json in list{
+		let transaction = try!(Transaction::from_info(try!(info::decode_transaction(json))));
+		if min_amount.map(|min| transaction.amount < min).unwrap_or(false)
+		|| max_amount.map(|max| transaction.amount > max).unwrap_or(false){
+			continue;
+		}
+		output.push(transaction);
+	}
+	let next_url = try!(util::get_next_link_from_headers(&res.headers));
+	Ok(QueryResult{
+		client: client.clone(),
+		data: output,
+		next_url: next_url,
+		min_amount: min_amount,
+		max_amount: max_amount
+	})
+}
+pub struct QueryResult{
+	client: Client,
+	data: Vec<Transaction>,
+	next_url: Option<String>,
+	min_amount: Option<f64>,
+	max_amount: Option<f64>
+}
+impl QueryResult{
+	///Pages filtered by amount can have fewer transactions than the page size, or none at all
+	pub fn get_transactions(&self) -> &Vec<Transaction>{
+		&self.data
+	}
+	pub fn has_next(&self) -> bool{
+		self.next_url.is_some()
+	}
+	pub fn next(&self) -> Option<CatapultResult<QueryResult>>{
+		self.next_url.as_ref().map(|ref url|{
+			get_transaction_list(&self.client, &url, (), self.min_amount, self.max_amount)
+		})
+	}
+}
+
+pub struct Query{
+	client: Client,
+	transaction_type: Option<TransactionType>,
+	from_time: Option<String>,
+	to_time: Option<String>,
+	min_amount: Option<f64>,
+	max_amount: Option<f64>,
+	size: Option<u32>
+}
+impl Query{
+	pub fn transaction_type(mut self, transaction_type: TransactionType) -> Query{
+		self.transaction_type = Some(transaction_type); self
+	}
+	///Only transactions at or after this time (ISO-8601)
+	pub fn from_time(mut self, time: &str) -> Query{
+		self.from_time = Some(time.to_owned()); self
+	}
+	///Only transactions before this time (ISO-8601)
+	pub fn to_time(mut self, time: &str) -> Query{
+		self.to_time = Some(time.to_owned()); self
+	}
+	///Catapult can't filter by amount, so this is applied to each page as it arrives
+	pub fn min_amount(mut self, amount: f64) -> Query{
+		self.min_amount = Some(amount); self
+	}
+	///Catapult can't filter by amount, so this is applied to each page as it arrives
+	pub fn max_amount(mut self, amount: f64) -> Query{
+		self.max_amount = Some(amount); self
+	}
+	pub fn size(mut self, size: u32) -> Query{
+		self.size = Some(size); self
+	}
+	pub fn submit(self) -> CatapultResult<QueryResult>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/account/transactions";
+		
+		let mut map = BTreeMap::new();
+		if let Some(transaction_type) = self.transaction_type{
+			map.insert("type".to_string(), transaction_type.to_string().to_json());
+		}
+		if let Some(from) = self.from_time{
+			map.insert("fromDate".to_string(), from.to_json());
+		}
+		if let Some(to) = self.to_time{
+			map.insert("toDate".to_string(), to.to_json());
+		}
+		if let Some(size) = self.size{
+			map.insert("size".to_string(), size.to_json());
+		}
+		let json = Json::Object(map);
+		
+		get_transaction_list(&self.client, &path, json, self.min_amount, self.max_amount)
+	}
+}
 
 struct Data{
 	balance: Lazy<String>,
@@ -32,6 +220,17 @@ impl Account{
 			}))
 		}
 	}
+	pub fn query_transactions(&self) -> Query{
+		Query{
+			client: self.client.clone(),
+			transaction_type: None,
+			from_time: None,
+			to_time: None,
+			min_amount: None,
+			max_amount: None,
+			size: None
+		}
+	}
 	
 	/* Getters */
 	pub fn get_balance(&self) -> CatapultResult<String>{
@@ -45,9 +244,33 @@ impl Account{
 
 mod info{
 	#![allow(non_snake_case)]
+	use rustc_serialize::json::Json;
+	use {util, CatapultResult};
+	
+	///"type" can't be a field name, so it is renamed to "transactionType"
+	pub fn decode_transaction(mut json: Json) -> CatapultResult<TransactionInfo>{
+		if let Json::Object(ref mut map) = json{
+			if let Some(transaction_type) = map.remove("type"){
+				map.insert("transactionType".to_owned(), transaction_type);
+			}
+		}
+		util::decode_json(json)
+	}
+	
 	#[derive(RustcDecodable)]
 	pub struct AccountInfo{
 		pub balance: String,
 		pub accountType: String
 	}
+	
+	#[derive(RustcDecodable)]
+	pub struct TransactionInfo{
+		pub id: String,
+		pub time: String,
+		pub amount: String,
+		pub transactionType: String,
+		pub units: Option<u64>,
+		pub productType: Option<String>,
+		pub number: Option<String>
+	}
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+use {CatapultResult, Client};
+use client::EmptyResponse;
+use voice::Voice;
+
+///Anything audio can be played to: a Call, Bridge, Conference or conference Member.
+///Lets the same prompt code work with any of them
+pub trait AudioTarget{
+	///`tag` is included in the playback events, so you can tell which audio finished
+	fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>;
+	fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>;
+	///Stops either an audio file playing, or a sentence being spoken
+	///This is the only way to stop audio in a loop
+	fn stop_audio(&self) -> CatapultResult<()>;
+}
+
+pub fn play_audio_file(client: &Client, path: &str, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
+	let json = json!({
+		"fileUrl" => (url),
+		"loopEnabled" => (loop_audio),
+		"tag" => (tag.map(|a|a.to_string()))
+	});
+	let _:EmptyResponse = try!(client.raw_post_request(path, (), &json));
+	Ok(())
+}
+pub fn speak_sentence(client: &Client, path: &str, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
+	let json = json!({
+		"sentence" => (sentence),
+		"loopEnabled" => (loop_audio),
+		"voice" => (voice.get_name()),
+		"tag" => (tag.map(|a|a.to_string()))
+	});
+	let _:EmptyResponse = try!(client.raw_post_request(path, (), &json));
+	Ok(())
+}
+pub fn stop_audio(client: &Client, path: &str) -> CatapultResult<()>{
+	let json = json!({
+		"fileUrl" => ""
+	});
+	let _:EmptyResponse = try!(client.raw_post_request(path, (), &json));
+	Ok(())
+}
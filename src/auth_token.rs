@@ -1,25 +1,125 @@
 use {CatapultResult, Endpoint};
-use client::JsonResponse;
+use client::{JsonResponse, EmptyResponse};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use self::info::AuthTokenInfo;
 
-#[derive(RustcDecodable)]
+mod info{
+	#[derive(RustcDecodable)]
+	pub struct AuthTokenInfo{
+		pub token: String,
+		pub expires: u64
+	}
+}
+
+#[derive(Clone)]
 pub struct AuthToken{
 	token: String,
-	expires: u64
+	expires: u64,
+	expires_at: SystemTime,
+	endpoint: Endpoint
 }
 
 impl AuthToken{
+	fn tokens_path(endpoint: &Endpoint) -> String{
+		let client = endpoint.get_client();
+		"users/".to_string() + &client.get_user_id() + "/domains/" + &endpoint.get_domain().get_id()
+		+ "/endpoints/" + &endpoint.get_id() + "/tokens"
+	}
 	pub fn create(endpoint: &Endpoint) -> CatapultResult<AuthToken>{
 		let client = endpoint.get_client();
-		let domain = endpoint.get_domain();
-		let path = "users/".to_string() + &client.get_user_id() + "/domains/" + &domain.get_id()
-		+ "/endpoints/" + &endpoint.get_id() + "/tokens";
-		let res:JsonResponse<AuthToken> = try!(client.raw_post_request(&path, (), ()));
-		Ok(res.body)
-	} 
+		//taken before the request, so the expiry time errs on the early side
+		let created = SystemTime::now();
+		let res:JsonResponse<AuthTokenInfo> = try!(client.raw_post_request(&Self::tokens_path(endpoint), (), ()));
+		Ok(AuthToken{
+			token: res.body.token,
+			expires: res.body.expires,
+			expires_at: created + Duration::from_secs(res.body.expires),
+			endpoint: endpoint.clone()
+		})
+	}
+	///Revokes the token, so it can't be used to register anymore
+	pub fn delete(&self) -> CatapultResult<()>{
+		let client = self.endpoint.get_client();
+		let path = Self::tokens_path(&self.endpoint) + "/" + &self.token;
+		let _:EmptyResponse = try!(client.raw_delete_request(&path, ()));
+		Ok(())
+	}
+	
+	/* Getters */
 	pub fn get_token(&self) -> String{
 		self.token.clone()
 	}
+	///Seconds the token was valid for when it was created
 	pub fn get_expires(&self) -> u64{
 		self.expires
 	}
-}
\ No newline at end of file
+	pub fn get_expires_at(&self) -> SystemTime{
+		self.expires_at
+	}
+	pub fn is_expired(&self) -> bool{
+		self.expires_within(Duration::from_secs(0))
+	}
+	pub fn expires_within(&self, duration: Duration) -> bool{
+		SystemTime::now() + duration >= self.expires_at
+	}
+	pub fn get_endpoint(&self) -> Endpoint{
+		self.endpoint.clone()
+	}
+}
+
+///Hands out one token per endpoint, creating a new one shortly before the old one expires
+#[derive(Clone)]
+pub struct TokenManager{
+	refresh_before: Duration,
+	tokens: Arc<Mutex<HashMap<String, Arc<Mutex<Option<AuthToken>>>>>>
+}
+impl TokenManager{
+	///Tokens are replaced once they expire within `refresh_before`
+	pub fn new(refresh_before: Duration) -> TokenManager{
+		TokenManager{
+			refresh_before: refresh_before,
+			tokens: Arc::new(Mutex::new(HashMap::new()))
+		}
+	}
+	///The endpoint's slot, created if needed. Slots nobody is using whose token
+	///has expired are dropped on the way
+	fn get_slot(&self, endpoint: &Endpoint) -> Arc<Mutex<Option<AuthToken>>>{
+		let mut tokens = self.tokens.lock().unwrap();
+		//slots are only cloned while the map is locked, so a count of 1 means nobody holds it
+		tokens.retain(|_, slot|{
+			Arc::strong_count(slot) > 1 || slot.lock().unwrap().as_ref().map_or(false, |token| !token.is_expired())
+		});
+		tokens.entry(endpoint.get_id())
+			.or_insert_with(|| Arc::new(Mutex::new(None)))
+			.clone()
+	}
+	///A token valid for at least `refresh_before`.
+	///Callers for the same endpoint wait for each other, so only one token is created at a time
+	pub fn get_token(&self, endpoint: &Endpoint) -> CatapultResult<AuthToken>{
+		let slot = self.get_slot(endpoint);
+		let mut cached = slot.lock().unwrap();
+		if let Some(ref token) = *cached{
+			if !token.expires_within(self.refresh_before){
+				return Ok(token.clone())
+			}
+		}
+		let token = try!(AuthToken::create(endpoint));
+		*cached = Some(token.clone());
+		Ok(token)
+	}
+	///Deletes the endpoint's cached token. The next get_token() creates a new one
+	pub fn revoke(&self, endpoint: &Endpoint) -> CatapultResult<()>{
+		let slot = self.get_slot(endpoint);
+		let token = slot.lock().unwrap().take();
+		match token{
+			Some(token) => token.delete(),
+			None => Ok(())
+		}
+	}
+	///Forgets every cached token without deleting them
+	pub fn clear(&self){
+		self.tokens.lock().unwrap().clear();
+	}
+}
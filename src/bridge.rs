@@ -1,12 +1,53 @@
 use client::{EmptyResponse, JsonResponse, Client};
-use CatapultResult;
+use {CatapultResult, CatapultError};
 use lazy::Lazy;
 use lazy::Lazy::*;
 use std::sync::{Arc, Mutex};
+use std::collections::BTreeMap;
 use util;
-use call::Call;
+use call::{self, Call};
 use self::info::BridgeInfo;
+use rustc_serialize::json::{self, Json, ToJson};
+use voice::Voice;
+use audio::{self, AudioTarget};
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum State{
+	///The bridge has less than two active calls
+	Created,
+	///Two or more calls are active in the bridge
+	Active,
+	///Audio is not being bridged between the calls
+	Hold,
+	Completed,
+	Error
+}
+impl State{
+	pub fn parse(state: &str) -> CatapultResult<State>{
+		Ok(match state{
+			"created" => State::Created,
+			"active" => State::Active,
+			"hold" => State::Hold,
+			"completed" => State::Completed,
+			"error" => State::Error,
+			state @ _ => return Err(CatapultError::unexpected(
+				&format!("unknown Bridge state: {}", state)
+			))
+		})
+	}
+	pub fn to_string(&self) -> &'static str{
+		use self::State::*;
+		match *self{
+			Created => "created",
+			Active => "active",
+			Hold => "hold",
+			Completed => "completed",
+			Error => "error"
+		}
+	}
+}
+
+#[derive(Clone)]
 pub struct Bridge{
 	id: String,
 	client: Client,
@@ -14,7 +55,7 @@ pub struct Bridge{
 }
 
 struct Data{
-	state: Lazy<String>,
+	state: Lazy<State>,
 	bridge_audio: Lazy<bool>,
 	created_time: Lazy<String>,
 	activated_time: Lazy<Option<String>>,
@@ -23,7 +64,7 @@ struct Data{
 impl Data{
 	fn from_info(info: &BridgeInfo) -> CatapultResult<Data>{
 		Ok(Data{
-			state: Available(info.state.clone()),
+			state: Available(try!(State::parse(&info.state))),
 			bridge_audio: Available(info.bridgeAudio),
 			created_time: Available(info.createdTime.clone()),
 			activated_time: Available(info.activatedTime.clone()),
@@ -46,6 +87,63 @@ mod info{
 	}
 }
 
+fn get_bridge_list<P: json::ToJson>(client: &Client, path: &str, params: P) -> CatapultResult<QueryResult>{
+	let res:JsonResponse<Vec<BridgeInfo>> = try!(client.raw_get_request(&path, params, ()));
+	let mut output = vec!();
+	for info in res.body{
+		output.push(Bridge{
+			id: info.id.clone(),
+			client: client.clone(),
+			data: Arc::new(Mutex::new(try!(Data::from_info(&info))))
+		});
+	}
+	let next_url = try!(util::get_next_link_from_headers(&res.headers));
+	Ok(QueryResult{
+		client: client.clone(),
+		data: output,
+		next_url: next_url
+	})
+}
+pub struct QueryResult{
+	client: Client,
+	data: Vec<Bridge>,
+	next_url: Option<String>
+}
+impl QueryResult{
+	pub fn get_bridges(&self) -> &Vec<Bridge>{
+		&self.data
+	}
+	pub fn has_next(&self) -> bool{
+		self.next_url.is_some()
+	}
+	pub fn next(&self) -> Option<CatapultResult<QueryResult>>{
+		self.next_url.as_ref().map(|ref url|{
+			get_bridge_list(&self.client, &url, ())
+		})
+	}
+}
+
+pub struct Query{
+	client: Client,
+	size: Option<u32>
+}
+impl Query{
+	pub fn size(mut self, size: u32) -> Query{
+		self.size = Some(size); self
+	}
+	pub fn submit(self) -> CatapultResult<QueryResult>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges";
+		
+		let mut map = BTreeMap::new();
+		if let Some(size) = self.size{
+			map.insert("size".to_string(), size.to_json());
+		}
+		let json = Json::Object(map);
+		
+		get_bridge_list(&self.client, &path, json)
+	}
+}
+
 impl Bridge{
 	pub fn load(&self) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id;
@@ -67,6 +165,12 @@ impl Bridge{
 			}))
 		}
 	}
+	pub fn query(client: &Client) -> Query{
+		Query{
+			client: client.clone(),
+			size: None
+		}
+	}
 	pub fn create(client: &Client, bridge_audio: bool, call_ids: &Vec<String>) -> CatapultResult<Bridge>{
 		let path = "users/".to_string() + &client.get_user_id() + "/bridges";
 		
@@ -91,6 +195,8 @@ impl Bridge{
 	}
 	
 	/* Actions */
+	///Replaces the calls in the bridge. Two handlers updating the same bridge can undo each other's changes,
+	///use add_call() and remove_call() instead when others may be changing it too
 	pub fn update(&self, bridge_audio: bool, call_ids: &Vec<String>) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id;
 		let json = json!({
@@ -101,31 +207,76 @@ impl Bridge{
 		Ok(())
 	}
 	
-	pub fn remove_all_calls(&self) -> CatapultResult<()>{
+	fn set_call_ids(&self, call_ids: &Vec<String>) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id;
 		let json = json!({
-			"callIds" => (Vec::<String>::new())
+			"callIds" => (call_ids)
 		});
 		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
 		Ok(())
 	}
-	pub fn enable_audio(&self, enable: bool) -> CatapultResult<()>{
+	///Fetches the calls currently in the bridge and lets `f` change the list before sending it back
+	fn modify_call_ids<F>(&self, f: F) -> CatapultResult<()>
+	where F: FnOnce(&mut Vec<String>){
+		self.client.with_bridge_lock(&self.id, ||{
+			let mut call_ids = vec!();
+			for call in try!(self.get_calls()){
+				if let call::State::Completed = try!(call.get_state()){
+					continue;
+				}
+				call_ids.push(call.get_id());
+			}
+			f(&mut call_ids);
+			self.set_call_ids(&call_ids)
+		})
+	}
+	///Adds a call without resending the other calls yourself.
+	///This reads the bridge's calls and writes them back, so it is only safe against other
+	///add_call()/remove_call() callers using the same Client (or a clone of it).
+	///Changes made through another Client or process, or through update() or remove_all_calls(), can still be lost
+	pub fn add_call(&self, call_id: &str) -> CatapultResult<()>{
+		self.modify_call_ids(|call_ids|{
+			if !call_ids.iter().any(|id| id == call_id){
+				call_ids.push(call_id.to_owned());
+			}
+		})
+	}
+	///Same limits as add_call()
+	pub fn remove_call(&self, call_id: &str) -> CatapultResult<()>{
+		self.modify_call_ids(|call_ids|{
+			call_ids.retain(|id| id != call_id);
+		})
+	}
+	pub fn remove_all_calls(&self) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id;
 		let json = json!({
-			"bridgeAudio" => (enable)
+			"callIds" => (Vec::<String>::new())
 		});
 		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
 		Ok(())
 	}
-	pub fn play_audio_file(&self, url: &str, loop_audio: bool) -> CatapultResult<()>{
-		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id + "/audio";
+	pub fn enable_audio(&self, enable: bool) -> CatapultResult<()>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id;
 		let json = json!({
-			"fileUrl" => (url),
-			"loopEnabled" => (loop_audio)
+			"bridgeAudio" => (enable)
 		});
 		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
 		Ok(())
 	}
+	fn audio_path(&self) -> String{
+		"users/".to_string() + &self.client.get_user_id() + "/bridges/" + &self.id + "/audio"
+	}
+	pub fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
+		audio::play_audio_file(&self.client, &self.audio_path(), url, loop_audio, tag)
+	}
+	pub fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
+		audio::speak_sentence(&self.client, &self.audio_path(), sentence, loop_audio, voice, tag)
+	}
+	///Stops either an audio file playing, or a sentence being spoken
+	///This is the only way to stop audio in a loop
+	pub fn stop_audio(&self) -> CatapultResult<()>{
+		audio::stop_audio(&self.client, &self.audio_path())
+	}
 	
 	/* Getters */
 	pub fn get_id(&self) -> String{
@@ -138,7 +289,7 @@ impl Bridge{
 		Call::get_calls_from_bridge(self)
 	}
 	
-	pub fn get_state(&self) -> CatapultResult<String>{
+	pub fn get_state(&self) -> CatapultResult<State>{
 		lazy_load!(self, state)
 	}
 	pub fn get_bridge_audio(&self) -> CatapultResult<bool>{
@@ -153,4 +304,16 @@ impl Bridge{
 	pub fn get_completed_time(&self) -> CatapultResult<Option<String>>{
 		lazy_load!(self, completed_time)
 	}
-}
\ No newline at end of file
+}
+
+impl AudioTarget for Bridge{
+	fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
+		Bridge::play_audio_file(self, url, loop_audio, tag)
+	}
+	fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
+		Bridge::speak_sentence(self, sentence, loop_audio, voice, tag)
+	}
+	fn stop_audio(&self) -> CatapultResult<()>{
+		Bridge::stop_audio(self)
+	}
+}
@@ -10,6 +10,8 @@ use self::info::CallInfo;
 use rustc_serialize::json::{ToJson, Json};
 use rustc_serialize::json;
 use voice::Voice;
+use phone_number::PhoneNumber;
+use audio::{self, AudioTarget};
 use std::collections::BTreeMap;
 
 #[derive(Clone, Debug)]
@@ -342,11 +344,11 @@ pub struct Query{
 	sort_order: Option<String>
 }
 impl Query{
-	pub fn from(mut self, from: &str) -> Query{
-		self.from = Some(from.to_string()); self 
+	pub fn from<N: Into<PhoneNumber>>(mut self, from: N) -> Query{
+		self.from = Some(from.into().get_e164()); self 
 	}
-	pub fn to(mut self, to: &str) -> Query{
-		self.to = Some(to.to_string()); self 
+	pub fn to<N: Into<PhoneNumber>>(mut self, to: N) -> Query{
+		self.to = Some(to.into().get_e164()); self 
 	}
 	pub fn state(mut self, state: State) -> Query{
 		self.state = Some(state); self
@@ -407,11 +409,12 @@ impl Call{
 			sort_order: None
 		}
 	}
-	pub fn build(client: &Client, from: &str, to: &str) -> CallBuilder{
+	pub fn build<F, T>(client: &Client, from: F, to: T) -> CallBuilder
+	where F: Into<PhoneNumber>, T: Into<PhoneNumber>{
 		CallBuilder{
 			client: client.clone(),
-			from: from.to_owned(),
-			to: to.to_owned(),
+			from: from.into().get_e164(),
+			to: to.into().get_e164(),
 			config: Config::new()
 		}
 	}
@@ -429,6 +432,30 @@ impl Call{
 		}
 		Ok(output)
 	}
+	pub fn get_by_id(client: &Client, id: &str) -> Call{
+		Call{
+			id: id.to_owned(),
+			client: client.clone(),
+			data: Arc::new(Mutex::new(Data{
+				active_time: NotLoaded,
+				bridge_id: NotLoaded,
+				callback_url: NotLoaded,
+				direction: NotLoaded,
+				from: NotLoaded,
+				to: NotLoaded,
+				recording_file_format: NotLoaded,
+				recording_enabled: NotLoaded,
+				start_time: NotLoaded,
+				state: NotLoaded,
+				transcription_enabled: NotLoaded,
+				display_name: NotLoaded,
+				preferred_id: NotLoaded,
+				withhold_caller_name: NotLoaded,
+				withhold_caller_number: NotLoaded,
+				end_time: NotLoaded
+			}))
+		}
+	}
 	pub fn from_call_event(event: &CallEvent) -> Call{
 		Call{
 			id: event.get_call_id(),
@@ -499,36 +526,19 @@ impl Call{
 			"recordingEnabled" => (enable)
 		}))
 	}
+	fn audio_path(&self) -> String{
+		"users/".to_string() + &self.client.get_user_id() + "/calls/" + &self.id + "/audio"
+	}
 	pub fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
-		let path = "users/".to_string() + &self.client.get_user_id() + "/calls/" + &self.id + "/audio";
-		let json = json!({
-			"fileUrl" => (url),
-			"loopEnabled" => (loop_audio),
-			"tag" => (tag.map(|a|a.to_string()))
-		});
-		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::play_audio_file(&self.client, &self.audio_path(), url, loop_audio, tag)
 	}
 	///Stops either an audio file playing, or a sentence being spoken
 	///This is the only way to stop audio in a loop
 	pub fn stop_audio(&self) -> CatapultResult<()>{
-		let path = "users/".to_string() + &self.client.get_user_id() + "/calls/" + &self.id + "/audio";
-		let json = json!({
-			"fileUrl" => ""
-		});
-		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::stop_audio(&self.client, &self.audio_path())
 	}
 	pub fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
-		let path = "users/".to_string() + &self.client.get_user_id() + "/calls/" + &self.id + "/audio";
-		let json = json!({
-			"sentence" => (sentence),
-			"loopEnabled" => (loop_audio),
-			"voice" => (voice.get_name()),
-			"tag" => (tag.map(|a|a.to_string()))
-		});
-		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::speak_sentence(&self.client, &self.audio_path(), sentence, loop_audio, voice, tag)
 	}
 	pub fn send_dtmf(&self, digits: &str) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/calls/" + &self.id + "/dtmf";
@@ -646,3 +656,15 @@ impl Call{
 		lazy_load!(self, end_time)
 	}
 }
+
+impl AudioTarget for Call{
+	fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
+		Call::play_audio_file(self, url, loop_audio, tag)
+	}
+	fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
+		Call::speak_sentence(self, sentence, loop_audio, voice, tag)
+	}
+	fn stop_audio(&self) -> CatapultResult<()>{
+		Call::stop_audio(self)
+	}
+}
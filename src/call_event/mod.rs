@@ -1,6 +1,7 @@
-use {CatapultError, CatapultResult, Client};
+use {CatapultError, CatapultResult, Client, PhoneNumber};
 use rustc_serialize::json;
 use call::Call;
+use number_info::{NumberInfo, NumberInfoCache};
 use self::info::CallEventInfo;
 
 mod incoming_event;
@@ -142,6 +143,24 @@ impl CallEvent{
 			_ => None
 		}
 	}
+	///The `from` number, if this event has one and it is a phone number (not a SIP uri or "anonymous")
+	fn get_from_number(&self) -> Option<PhoneNumber>{
+		self.get_from().and_then(|from| PhoneNumber::parse(&from).ok())
+	}
+	///Looks up the caller name of the `from` number. None if this event has no `from` phone number
+	pub fn get_caller_info(&self) -> CatapultResult<Option<NumberInfo>>{
+		match self.get_from_number(){
+			Some(from) => Ok(Some(try!(NumberInfo::get(&self.client, from)))),
+			None => Ok(None)
+		}
+	}
+	///Same as get_caller_info(), but uses the cache to avoid repeated lookups
+	pub fn get_caller_info_cached(&self, cache: &NumberInfoCache) -> CatapultResult<Option<NumberInfo>>{
+		match self.get_from_number(){
+			Some(from) => Ok(Some(try!(cache.get(from)))),
+			None => Ok(None)
+		}
+	}
 }
 
 
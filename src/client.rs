@@ -7,8 +7,10 @@ use hyper::client::response::Response;
 use rustc_serialize::{Decodable, json};
 use rustc_serialize::json::Json;
 use hyper::Url;
-use hyper::client::RequestBuilder;
+use hyper::client::{Body, RequestBuilder};
 use std::sync::{Mutex, Arc};
+use std::time::Duration;
+use std::collections::HashMap;
 use {util, application, message, call};
 use environment::Environment;
 use domain::Domain;
@@ -16,11 +18,19 @@ use call_event::CallEvent;
 use application::Application;
 use account::Account;
 use message_event::MessageEvent;
-use number::Number;
-use media::{Media, ToBytes};
-use conference::{Conference, ConferenceBuilder};
+use number::{self, AvailableNumber, Number};
+use number_info::NumberInfo;
+use media::{self, Media, ToBytes};
+use conference::{self, Conference, ConferenceBuilder};
+use conference_event::ConferenceEvent;
 use call::{CallBuilder, Call};
+use bridge::{self, Bridge};
 use message::{Message};
+use compliance::{Compliance, ComplianceBuilder, OptOutStore};
+use conversation::Conversation;
+use scheduler::{Scheduler, ScheduleStore};
+use provisioning::ProvisioningBuilder;
+use phone_number::PhoneNumber;
 use hyper::client::pool::Config;
 
 #[derive(Clone)]
@@ -34,7 +44,9 @@ struct Data{
 	api_token: String,
 	api_secret: String,
 	api_version: String,
-	environment: Environment
+	environment: Environment,
+	opt_out_store: Option<Arc<OptOutStore>>,
+	bridge_locks: HashMap<String, Arc<Mutex<()>>>
 }
 
 pub trait ApiResponse<T>{
@@ -57,6 +69,23 @@ where T: Decodable{
 	}
 }
 
+///For bodies that need adjusting before they can be decoded
+#[derive(Debug)]
+pub struct RawJsonResponse{
+	pub headers: Headers,
+	pub body: Json
+}
+impl ApiResponse<RawJsonResponse> for RawJsonResponse{
+	fn new(res: &mut Response) -> CatapultResult<RawJsonResponse>{
+		let mut data = String::new();
+		try!(res.read_to_string(&mut data));
+		Ok(RawJsonResponse{
+			headers: res.headers.clone(),
+			body: try!(util::parse_json(&data))
+		})
+	}
+}
+
 pub struct ByteResponse{
 	pub headers: Headers,
 	pub body: Vec<u8>
@@ -87,6 +116,22 @@ impl ApiResponse<EmptyResponse> for EmptyResponse{
 
 pub trait ToBody{
 	fn to_body(self) -> Vec<u8>;
+	fn content_type(&self) -> ContentType{
+		ContentType::json()
+	}
+}
+///A body sent with its own content type instead of JSON
+pub struct TypedBody{
+	pub content_type: ContentType,
+	pub data: Vec<u8>
+}
+impl ToBody for TypedBody{
+	fn to_body(self) -> Vec<u8>{
+		self.data
+	}
+	fn content_type(&self) -> ContentType{
+		self.content_type.clone()
+	}
 }
 impl ToBody for Vec<u8>{
 	fn to_body(self) -> Vec<u8>{
@@ -119,7 +164,9 @@ impl Client{
 				api_token: api_token.to_string(),
 				api_secret: api_secret.to_string(),
 				api_version: "v1".to_string(),
-				environment: Environment::Production
+				environment: Environment::Production,
+				opt_out_store: None,
+				bridge_locks: HashMap::new()
 			})),
 			hyper_client: Arc::new(hyper::Client::with_pool_config(Config{max_idle: 1024*4}))
 		}
@@ -162,31 +209,56 @@ impl Client{
 		})
 	}
 	
+	///Uploads `length` bytes from `reader` without buffering them in memory
+	pub fn raw_put_stream_request<Params, Output>(&self, path: &str, params: Params, reader: &mut Read, length: u64, content_type: ContentType) -> CatapultResult<Output>
+	where Params: json::ToJson, Output: ApiResponse<Output>{
+		let mut res = try!(self.send_request(path, params, content_type, Body::SizedBody(reader, length), |client, url|{
+			client.put(url)
+		}));
+		Output::new(&mut res)
+	}
+	///The response body is left unread so it can be streamed
+	pub fn raw_get_stream_request<Params>(&self, path: &str, params: Params) -> CatapultResult<Response>
+	where Params: json::ToJson{
+		let body: &[u8] = &[];
+		self.send_request(path, params, ContentType::json(), Body::BufBody(body, 0), |client, url|{
+			client.get(url)
+		})
+	}
+	
 	fn raw_request<Input, Params, Output, Type>(&self, path: &str, params: Params, body: Input, req_type: Type) -> CatapultResult<Output>
 	where 
 	 	Input: ToBody,
 		Output: ApiResponse<Output>,
 		Params: json::ToJson,
 		Type: FnOnce(& hyper::Client, Url) -> RequestBuilder
+	{
+		let content_type = body.content_type();
+		let vec_body: Vec<u8> = body.to_body();
+		let mut res = try!(self.send_request(path, params, content_type, Body::BufBody(&vec_body, vec_body.len()), req_type));
+		Output::new(&mut res)
+	}
+	fn send_request<'a, Params, Type>(&'a self, path: &str, params: Params, content_type: ContentType, body: Body<'a>, req_type: Type) -> CatapultResult<Response>
+	where 
+		Params: json::ToJson,
+		Type: FnOnce(&'a hyper::Client, Url) -> RequestBuilder<'a>
 	{
 		let mut url = try!(self.make_absolute_url(path));
 		util::set_query_params_from_json(&mut url, &params.to_json());
-		let vec_body: Vec<u8> = body.to_body();
 		let req = 
 			req_type(&self.hyper_client, url)
 			.header(Authorization(Basic{
 				username: self.get_api_token(),
 				password: Some(self.get_api_secret())
 			}))
-			.header(ContentType::json())
-			.body(&vec_body as &[u8]);
+			.header(content_type)
+			.body(body);
 		
 		let mut res = try!(req.send());
 		
 		let status = res.status_raw().0;
 		if status >= 200 && status < 400{
-			let output = Output::new(&mut res);
-			output
+			Ok(res)
 		}else{
 			let mut data = String::new();
 			try!(res.read_to_string(&mut data));
@@ -199,6 +271,11 @@ impl Client{
 		let mut data = self.data.lock().unwrap();
 		data.environment = env;
 	}
+	///Messages sent to numbers in this store are refused with CatapultError::OptedOut
+	pub fn set_opt_out_store(&self, store: Option<Arc<OptOutStore>>){
+		let mut data = self.data.lock().unwrap();
+		data.opt_out_store = store;
+	}
 	
 	/* Getters */
 	pub fn get_user_id(&self) -> String{
@@ -213,6 +290,33 @@ impl Client{
 		let data = self.data.lock().unwrap();
 		data.api_secret.clone()
 	}
+	pub fn get_opt_out_store(&self) -> Option<Arc<OptOutStore>>{
+		let data = self.data.lock().unwrap();
+		data.opt_out_store.clone()
+	}
+	
+	///Runs `f` while holding a lock for `bridge_id`, see Bridge::add_call().
+	///Only callers using this Client or its clones wait for each other. Other clients
+	///and processes are not stopped, since Catapult has no lock for a bridge
+	pub(crate) fn with_bridge_lock<T, F>(&self, bridge_id: &str, f: F) -> T
+	where F: FnOnce() -> T{
+		let lock = {
+			let mut data = self.data.lock().unwrap();
+			data.bridge_locks.entry(bridge_id.to_owned())
+				.or_insert_with(|| Arc::new(Mutex::new(())))
+				.clone()
+		};
+		let output = {
+			let _guard = lock.lock().unwrap();
+			f()
+		};
+		let mut data = self.data.lock().unwrap();
+		//only the map and this function hold it, so nobody else is waiting
+		if Arc::strong_count(&lock) == 2{
+			data.bridge_locks.remove(bridge_id);
+		}
+		output
+	}
 	
 	/* Object Helpers */
 	
@@ -232,26 +336,60 @@ impl Client{
 		Application::get(self, id)
 	}
 	
+	//Bridge
+	pub fn create_bridge(&self, bridge_audio: bool, call_ids: &Vec<String>) -> CatapultResult<Bridge>{
+		Bridge::create(self, bridge_audio, call_ids)
+	}
+	pub fn get_bridge(&self, id: &str) -> Bridge{
+		Bridge::get_by_id(self, id)
+	}
+	pub fn query_bridges(&self) -> bridge::Query{
+		Bridge::query(self)
+	}
+	
 	//Call
-	pub fn build_call(&self, from: &str, to: &str) -> CallBuilder{
+	pub fn build_call<F, T>(&self, from: F, to: T) -> CallBuilder
+	where F: Into<PhoneNumber>, T: Into<PhoneNumber>{
 		Call::build(self, from, to)
 	}
 	pub fn query_calls(&self) -> call::Query{
 		Call::query(self)
 	}
+	pub fn get_call(&self, id: &str) -> Call{
+		Call::get_by_id(self, id)
+	}
 	
 	//CallEvent
 	pub fn parse_call_event(&self, data: &str) -> CatapultResult<CallEvent>{
 		CallEvent::parse(self, data)
 	}
 	
+	//Compliance
+	pub fn build_compliance(&self) -> ComplianceBuilder{
+		Compliance::build(self)
+	}
+	
+	//Conversation
+	pub fn get_conversation<O, T>(&self, our_number: O, their_number: T) -> Conversation
+	where O: Into<PhoneNumber>, T: Into<PhoneNumber>{
+		Conversation::new(self, our_number, their_number)
+	}
+	
 	//Conference
-	pub fn build_conference(&self, from: &str) -> ConferenceBuilder{
+	pub fn build_conference<N: Into<PhoneNumber>>(&self, from: N) -> ConferenceBuilder{
 		Conference::build(self, from)
 	}
 	pub fn get_conference(&self, id: &str) -> Conference{
 		Conference::get(self, id)
 	}
+	pub fn query_conferences(&self) -> conference::Query{
+		Conference::query(self)
+	}
+	
+	//ConferenceEvent
+	pub fn parse_conference_event(&self, data: &str) -> CatapultResult<ConferenceEvent>{
+		ConferenceEvent::parse(self, data)
+	}
 	
 	// Domain
 	pub fn create_domain(&self, name: &str) -> CatapultResult<Domain>{
@@ -272,14 +410,33 @@ impl Client{
 	where T: ToBytes{
 		Media::create(self, filename, data)
 	}
+	pub fn create_media_with_content_type<T>(&self, filename: &str, data: T, content_type: &str) -> CatapultResult<Media>
+	where T: ToBytes{
+		Media::create_with_content_type(self, filename, data, content_type)
+	}
+	pub fn create_media_from_reader<R>(&self, filename: &str, reader: R, content_length: u64, content_type: &str) -> CatapultResult<Media>
+	where R: Read{
+		Media::create_from_reader(self, filename, reader, content_length, content_type)
+	}
 	pub fn get_media(&self, filename: &str) -> Media{
 		Media::get(self, filename)
 	}
+	pub fn list_media(&self) -> media::Query{
+		Media::query(self)
+	}
+	pub fn delete_media_older_than(&self, age: Duration) -> CatapultResult<Vec<(Media, CatapultResult<()>)>>{
+		Media::delete_older_than(self, age)
+	}
 	
 	// Message
-	pub fn build_message(&self, from: &str, to: &str, text: &str) -> message::PendingMessage{
+	pub fn build_message<F, T>(&self, from: F, to: T, text: &str) -> message::PendingMessage
+	where F: Into<PhoneNumber>, T: Into<PhoneNumber>{
 		Message::build(self, from, to, text)
 	}
+	pub fn build_group_message<F, I>(&self, from: F, to: I, text: &str) -> message::PendingMessage
+	where F: Into<PhoneNumber>, I: IntoIterator, I::Item: Into<PhoneNumber>{
+		Message::build_group(self, from, to, text)
+	}
 	pub fn batch_send_messages<I>(&self, iter: I) -> CatapultResult<Vec<CatapultResult<Message>>>
 	where I: IntoIterator<Item = message::PendingMessage>{
 		message::PendingMessage::batch_send(self, iter)
@@ -291,6 +448,11 @@ impl Client{
 		Message::query(self)
 	}
 	
+	// Scheduler
+	pub fn create_scheduler(&self, store: Arc<ScheduleStore>) -> Scheduler{
+		Scheduler::new(self, store)
+	}
+	
 	// MessageEvent
 	pub fn parse_message_event(&self, event: &str) -> CatapultResult<MessageEvent>{
 		MessageEvent::parse(self, event)
@@ -300,4 +462,36 @@ impl Client{
 	pub fn get_number_by_id(&self, id: &str) -> Number{
 		Number::by_id(self, id)
 	}
+	///Looks up one of your numbers, eg: `client.get_number_by_number(&"+19195551234".parse()?)`
+	pub fn get_number_by_number<N: Into<PhoneNumber>>(&self, number: N) -> CatapultResult<Number>{
+		Number::by_number(self, number)
+	}
+	pub fn query_numbers(&self) -> number::Query{
+		Number::query(self)
+	}
+	pub fn search_available_local(&self, quantity: u32, search: number::Search) -> CatapultResult<Vec<AvailableNumber>>{
+		AvailableNumber::search_local(self, quantity, search)
+	}
+	pub fn search_available_toll_free(&self, quantity: u32, search: number::TollFreeSearch) -> CatapultResult<Vec<AvailableNumber>>{
+		AvailableNumber::search_toll_free(self, quantity, search)
+	}
+	pub fn search_and_allocate_local(&self, quantity: u32, search: number::Search) -> CatapultResult<Vec<Number>>{
+		Number::search_and_allocate_local(self, quantity, search)
+	}
+	pub fn search_and_allocate_toll_free(&self, quantity: u32, search: number::TollFreeSearch) -> CatapultResult<Vec<Number>>{
+		Number::search_and_allocate_toll_free(self, quantity, search)
+	}
+	pub fn order_number<N: Into<PhoneNumber>>(&self, number: N) -> CatapultResult<Number>{
+		Number::order(self, number)
+	}
+	
+	// NumberInfo
+	pub fn get_number_info<N: Into<PhoneNumber>>(&self, number: N) -> CatapultResult<NumberInfo>{
+		NumberInfo::get(self, number)
+	}
+	
+	// Provisioning
+	pub fn build_provisioning(&self, app: &Application, quantity: u32, search: number::Search) -> ProvisioningBuilder{
+		ProvisioningBuilder::new(self, app, quantity, search)
+	}
 }
\ No newline at end of file
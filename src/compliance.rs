@@ -0,0 +1,169 @@
+use {CatapultResult, Client, PhoneNumber};
+use message_event::MessageEvent;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+///The form numbers are passed to an OptOutStore in, so +19195551234 and 9195551234 match.
+///Numbers that aren't valid phone numbers, such as short codes, are only trimmed
+pub fn normalize_number(number: &str) -> String{
+	match PhoneNumber::parse(number){
+		Ok(number) => number.get_e164(),
+		Err(_) => number.trim().to_owned()
+	}
+}
+
+///Storage for numbers that have opted out of receiving messages.
+///Implement this to keep the opt-out list somewhere persistent.
+///Numbers are normalized with normalize_number() before they reach the store
+pub trait OptOutStore: Send + Sync{
+	fn is_opted_out(&self, number: &str) -> CatapultResult<bool>;
+	fn opt_out(&self, number: &str) -> CatapultResult<()>;
+	fn opt_in(&self, number: &str) -> CatapultResult<()>;
+}
+
+///Keeps the opt-out list in memory. It is lost when the process exits.
+pub struct MemoryOptOutStore{
+	numbers: Mutex<HashSet<String>>
+}
+impl MemoryOptOutStore{
+	pub fn new() -> MemoryOptOutStore{
+		MemoryOptOutStore{
+			numbers: Mutex::new(HashSet::new())
+		}
+	}
+}
+impl OptOutStore for MemoryOptOutStore{
+	fn is_opted_out(&self, number: &str) -> CatapultResult<bool>{
+		Ok(self.numbers.lock().unwrap().contains(number))
+	}
+	fn opt_out(&self, number: &str) -> CatapultResult<()>{
+		self.numbers.lock().unwrap().insert(number.to_owned());
+		Ok(())
+	}
+	fn opt_in(&self, number: &str) -> CatapultResult<()>{
+		self.numbers.lock().unwrap().remove(number);
+		Ok(())
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Keyword{
+	///STOP, STOPALL, UNSUBSCRIBE, CANCEL, END, QUIT
+	Stop,
+	///START, YES, UNSTOP
+	Start,
+	///HELP, INFO
+	Help
+}
+impl Keyword{
+	///The whole message (ignoring case and surrounding whitespace) must be the keyword
+	pub fn parse(text: &str) -> Option<Keyword>{
+		match text.trim().to_uppercase().as_ref(){
+			"STOP" | "STOPALL" | "UNSUBSCRIBE" | "CANCEL" | "END" | "QUIT" => Some(Keyword::Stop),
+			"START" | "YES" | "UNSTOP" => Some(Keyword::Start),
+			"HELP" | "INFO" => Some(Keyword::Help),
+			_ => None
+		}
+	}
+}
+
+pub struct ComplianceBuilder{
+	client: Client,
+	store: Option<Arc<OptOutStore>>,
+	stop_text: String,
+	start_text: String,
+	help_text: String
+}
+impl ComplianceBuilder{
+	///Defaults to a MemoryOptOutStore
+	pub fn store(mut self, store: Arc<OptOutStore>) -> Self{
+		self.store = Some(store); self
+	}
+	pub fn stop_text(mut self, text: &str) -> Self{
+		self.stop_text = text.to_owned(); self
+	}
+	pub fn start_text(mut self, text: &str) -> Self{
+		self.start_text = text.to_owned(); self
+	}
+	pub fn help_text(mut self, text: &str) -> Self{
+		self.help_text = text.to_owned(); self
+	}
+	///Registers the store with the client. From then on, messages sent with this
+	///client to an opted-out number fail with CatapultError::OptedOut
+	pub fn create(self) -> Compliance{
+		let store = self.store.unwrap_or_else(|| Arc::new(MemoryOptOutStore::new()));
+		self.client.set_opt_out_store(Some(store.clone()));
+		Compliance{
+			client: self.client,
+			store: store,
+			stop_text: self.stop_text,
+			start_text: self.start_text,
+			help_text: self.help_text
+		}
+	}
+}
+
+pub struct Compliance{
+	client: Client,
+	store: Arc<OptOutStore>,
+	stop_text: String,
+	start_text: String,
+	help_text: String
+}
+impl Compliance{
+	pub fn build(client: &Client) -> ComplianceBuilder{
+		ComplianceBuilder{
+			client: client.clone(),
+			store: None,
+			stop_text: "You have been unsubscribed and will receive no further messages. Reply START to resubscribe.".to_owned(),
+			start_text: "You have been resubscribed. Reply STOP to unsubscribe.".to_owned(),
+			help_text: "Reply STOP to unsubscribe.".to_owned()
+		}
+	}
+
+	///Checks an inbound message for a keyword, updates the opt-out list and sends the auto-reply.
+	///Returns the keyword that was handled, if any.
+	pub fn handle_event(&self, event: &MessageEvent) -> CatapultResult<Option<Keyword>>{
+		if event.is_outbound(){
+			return Ok(None)
+		}
+		let keyword = match Keyword::parse(&event.get_text()){
+			Some(keyword) => keyword,
+			None => return Ok(None)
+		};
+		let number = normalize_number(&event.get_from());
+		match keyword{
+			Keyword::Stop => {
+				//the confirmation is the last message they receive, so send it before opting out
+				try!(self.reply(event, &self.stop_text));
+				try!(self.store.opt_out(&number));
+			},
+			Keyword::Start => {
+				try!(self.store.opt_in(&number));
+				try!(self.reply(event, &self.start_text));
+			},
+			Keyword::Help => {
+				try!(self.reply(event, &self.help_text));
+			}
+		}
+		Ok(Some(keyword))
+	}
+	///Carriers require these replies even to opted-out numbers, so the opt-out check is skipped
+	fn reply(&self, event: &MessageEvent, text: &str) -> CatapultResult<()>{
+		try!(event.build_reply(text)
+			.skip_opt_out_check()
+			.create());
+		Ok(())
+	}
+
+	/* Getters */
+	pub fn get_client(&self) -> Client{
+		self.client.clone()
+	}
+	pub fn get_store(&self) -> Arc<OptOutStore>{
+		self.store.clone()
+	}
+	pub fn is_opted_out<N: Into<PhoneNumber>>(&self, number: N) -> CatapultResult<bool>{
+		self.store.is_opted_out(&number.into().get_e164())
+	}
+}
@@ -7,15 +7,29 @@ use lazy::Lazy::*;
 use rustc_serialize::json::Json;
 use rustc_serialize::json::ToJson;
 use conference::Conference;
+use call::Call;
 use std::collections::BTreeMap;
 use self::info::MemberInfo;
 use voice::Voice;
+use audio::{self, AudioTarget};
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum State{
 	Active,
 	Completed
 }
+impl State{
+	pub fn parse(state: &str) -> CatapultResult<State>{
+		Ok(match state{
+			"active" => State::Active,
+			"completed" => State::Completed,
+			state @ _ => return Err(CatapultError::unexpected(
+				&format!("unknown member state: {}", state)
+			))
+		})
+	}
+}
+#[derive(Clone)]
 pub struct Member{
 	id: String,
 	conf: Conference,
@@ -68,48 +82,20 @@ impl Member{
 		self.data.lock().unwrap().state = Available(State::Completed);
 		Ok(())
 	}
+	fn audio_path(&self) -> String{
+		"users/".to_string() + &self.conf.get_client().get_user_id() + "/conferences/"
+			+ &self.conf.get_id() + "/members/" + &self.id + "/audio"
+	}
 	pub fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
-		let client = self.conf.get_client();
-		let user_id = client.get_user_id();
-		let conf_id = self.conf.get_id();
-		let id = self.get_id();
-		let path = "users/".to_string() + &user_id + "/conferences/" + &conf_id + "/members/" + &id + "/audio";
-		let json = json!({
-			"sentence" => (sentence),
-			"loopEnabled" => (loop_audio),
-			"voice" => (voice.get_name()),
-			"tag" => (tag.map(|a|a.to_string()))
-		});
-		let _:EmptyResponse = try!(client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::speak_sentence(&self.conf.get_client(), &self.audio_path(), sentence, loop_audio, voice, tag)
 	}
 	pub fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
-		let client = self.conf.get_client();
-		let user_id = client.get_user_id();
-		let conf_id = self.conf.get_id();
-		let id = self.get_id();
-		let path = "users/".to_string() + &user_id + "/conferences/" + &conf_id + "/members/" + &id + "/audio";
-		let json = json!({
-			"fileUrl" => (url),
-			"loopEnabled" => (loop_audio),
-			"tag" => (tag.map(|a|a.to_string()))
-		});
-		let _:EmptyResponse = try!(client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::play_audio_file(&self.conf.get_client(), &self.audio_path(), url, loop_audio, tag)
 	}
 	///Stops either an audio file playing, or a sentence being spoken
 	///This is the only way to stop audio in a loop
 	pub fn stop_audio(&self) -> CatapultResult<()>{
-		let client = self.conf.get_client();
-		let user_id = client.get_user_id();
-		let conf_id = self.conf.get_id();
-		let id = self.get_id();
-		let path = "users/".to_string() + &user_id + "/conferences/" + &conf_id + "/members/" + &id + "/audio";
-		let json = json!({
-			"fileUrl" => ""
-		});
-		let _:EmptyResponse = try!(client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::stop_audio(&self.conf.get_client(), &self.audio_path())
 	}
 	
 	/* Setters */
@@ -175,6 +161,32 @@ impl Member{
 		}
 		Ok(try!(self.data.lock().unwrap().state.get()).clone())
 	}
+	///The id of the call that joined the conference as this member
+	pub fn get_call_id(&self) -> CatapultResult<String>{
+		if !self.data.lock().unwrap().call_id.available(){
+			try!(self.load());
+		}
+		Ok(try!(self.data.lock().unwrap().call_id.get()).clone())
+	}
+	pub fn get_call(&self) -> CatapultResult<Call>{
+		Ok(Call::get_by_id(&self.conf.get_client(), &try!(self.get_call_id())))
+	}
+	pub fn get(conf: &Conference, id: &str) -> Member{
+		Member{
+			id: id.to_owned(),
+			conf: conf.clone(),
+			data: Arc::new(Mutex::new(Data{
+				added_time: NotLoaded,
+				removed_time: NotLoaded,
+				join_tone: NotLoaded,
+				leaving_tone: NotLoaded,
+				mute: NotLoaded,
+				hold: NotLoaded,
+				state: NotLoaded,
+				call_id: NotLoaded
+			}))
+		}
+	}
 	pub fn list_members_from_conference(conf: &Conference) -> CatapultResult<Vec<Member>>{
 		let client = conf.get_client();
 		let path = "users/".to_string() + &client.get_user_id() + "/conferences/"
@@ -199,24 +211,20 @@ struct Data{
 	leaving_tone: Lazy<bool>,
 	mute: Lazy<bool>,
 	hold: Lazy<bool>,
-	state: Lazy<State>
+	state: Lazy<State>,
+	call_id: Lazy<String>
 }
 impl Data{
 	fn from_info(info: &MemberInfo) -> CatapultResult<Data>{
 		Ok(Data{
+			call_id: Available(try!(util::get_id_from_location_url(&info.call))),
 			added_time: Available(info.addedTime.to_owned()),
 			removed_time: Available(info.removedTime.to_owned()),
 			join_tone: Available(info.joinTone),
 			leaving_tone: Available(info.leavingTone),
 			mute: Available(info.mute),
 			hold: Available(info.hold),
-			state: Available(match info.state.as_ref(){
-				"active" => State::Active,
-				"completed" => State::Completed,
-				state @ _ => return Err(CatapultError::unexpected(
-					&format!("unknown member state: {}", state)
-				))
-			}),
+			state: Available(try!(State::parse(&info.state))),
 		})
 	}
 }
@@ -232,7 +240,9 @@ mod info{
 		pub mute: bool,
 		pub state: String,
 		pub joinTone: bool,
-		pub leavingTone: bool
+		pub leavingTone: bool,
+		///url of the member's call
+		pub call: String
 	}
 }
 pub struct MemberBuilder{
@@ -289,8 +299,21 @@ impl MemberBuilder{
 				hold: Available(self.hold),
 				state: NotLoaded,
 				added_time: NotLoaded,
-				removed_time: NotLoaded
+				removed_time: NotLoaded,
+				call_id: Available(self.call_id.clone())
 			}))
 		})
 	}
-}
\ No newline at end of file
+}
+
+impl AudioTarget for Member{
+	fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
+		Member::play_audio_file(self, url, loop_audio, tag)
+	}
+	fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
+		Member::speak_sentence(self, sentence, loop_audio, voice, tag)
+	}
+	fn stop_audio(&self) -> CatapultResult<()>{
+		Member::stop_audio(self)
+	}
+}
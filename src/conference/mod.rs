@@ -1,18 +1,22 @@
 mod member;
+mod moderation;
 
 pub use self::member::{Member, MemberBuilder};
+pub use self::moderation::{ModerationReport, Moderator};
+pub use self::member::State as MemberState;
 
-use {CatapultResult, CatapultError};
+use {CatapultResult, CatapultError, PhoneNumber};
 use client::{EmptyResponse, JsonResponse, Client};
 use std::sync::{Arc, Mutex};
 use util;
 use lazy::Lazy;
 use lazy::Lazy::*;
 use self::info::ConferenceInfo;
-use rustc_serialize::json::Json;
+use rustc_serialize::json::{self, Json};
 use rustc_serialize::json::ToJson;
 use std::collections::BTreeMap;
 use voice::Voice;
+use audio::{self, AudioTarget};
 
 #[derive(Clone, Debug)]
 pub enum State{
@@ -24,6 +28,26 @@ pub enum State{
 	///Once the conference is completed, it can no longer be used.
 	Completed
 }
+impl State{
+	pub fn parse(state: &str) -> CatapultResult<State>{
+		Ok(match state{
+			"created" => State::Created,
+			"active" => State::Active,
+			"completed" => State::Completed,
+			state @ _ => return Err(CatapultError::unexpected(
+				&format!("unknown Conference state: {}", state)
+			))
+		})
+	}
+	pub fn to_string(&self) -> &'static str{
+		use self::State::*;
+		match *self{
+			Created => "created",
+			Active => "active",
+			Completed => "completed"
+		}
+	}
+}
 
 struct Data{
 	active_members: Lazy<u64>,
@@ -44,14 +68,7 @@ impl Data{
 			active_members: Available(info.activeMembers),
 			created_time: Available(info.createdTime.to_owned()),
 			from: Available(info.from.to_owned()),
-			state: Available(match info.state.as_ref(){
-				"created" => State::Created,
-				"active" => State::Active,
-				"completed" => State::Completed,
-				state @ _ => return Err(CatapultError::unexpected(
-					&format!("unknown Conference state: {}", state)
-				))
-			}),
+			state: Available(try!(State::parse(&info.state))),
 			callback_http_method: Available(info.callbackHttpMethod.to_owned()),
 			hold: Available(info.hold),
 			mute: Available(info.mute),
@@ -82,6 +99,93 @@ mod info{
 	}
 }
 
+fn get_conference_list<P: json::ToJson>(client: &Client, path: &str, params: P) -> CatapultResult<QueryResult>{
+	let res:JsonResponse<Vec<ConferenceInfo>> = try!(client.raw_get_request(&path, params, ()));
+	let mut output = vec!();
+	for info in res.body{
+		output.push(Conference{
+			id: info.id.clone(),
+			client: client.clone(),
+			data: Arc::new(Mutex::new(try!(Data::from_info(&info))))
+		});
+	}
+	let next_url = try!(util::get_next_link_from_headers(&res.headers));
+	Ok(QueryResult{
+		client: client.clone(),
+		data: output,
+		next_url: next_url
+	})
+}
+pub struct QueryResult{
+	client: Client,
+	data: Vec<Conference>,
+	next_url: Option<String>
+}
+impl QueryResult{
+	pub fn get_conferences(&self) -> &Vec<Conference>{
+		&self.data
+	}
+	pub fn has_next(&self) -> bool{
+		self.next_url.is_some()
+	}
+	pub fn next(&self) -> Option<CatapultResult<QueryResult>>{
+		self.next_url.as_ref().map(|ref url|{
+			get_conference_list(&self.client, &url, ())
+		})
+	}
+}
+
+pub struct Query{
+	client: Client,
+	state: Option<State>,
+	from_time: Option<String>,
+	to_time: Option<String>,
+	size: Option<u32>,
+	sort_order: Option<String>
+}
+impl Query{
+	pub fn state(mut self, state: State) -> Query{
+		self.state = Some(state); self
+	}
+	///Only conferences created at or after this time (ISO-8601)
+	pub fn from_time(mut self, time: &str) -> Query{
+		self.from_time = Some(time.to_owned()); self
+	}
+	///Only conferences created at or before this time (ISO-8601)
+	pub fn to_time(mut self, time: &str) -> Query{
+		self.to_time = Some(time.to_owned()); self
+	}
+	pub fn size(mut self, size: u32) -> Query{
+		self.size = Some(size); self
+	}
+	pub fn sort_desc(mut self) -> Query{
+		self.sort_order = Some("desc".to_string()); self
+	}
+	pub fn submit(self) -> CatapultResult<QueryResult>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/conferences";
+		
+		let mut map = BTreeMap::new();
+		if let Some(state) = self.state{
+			map.insert("state".to_string(), state.to_string().to_json());
+		}
+		if let Some(from) = self.from_time{
+			map.insert("fromDateTime".to_string(), from.to_json());
+		}
+		if let Some(to) = self.to_time{
+			map.insert("toDateTime".to_string(), to.to_json());
+		}
+		if let Some(size) = self.size{
+			map.insert("size".to_string(), size.to_json());
+		}
+		if let Some(sort_order) = self.sort_order{
+			map.insert("sortOrder".to_string(), sort_order.to_json());
+		}
+		let json = Json::Object(map);
+		
+		get_conference_list(&self.client, &path, json)
+	}
+}
+
 pub struct ConferenceBuilder{
 	client: Client,
 	from: String,
@@ -205,10 +309,20 @@ impl Conference{
 			}))
 		}
 	}
-	pub fn build(client: &Client, from: &str) -> ConferenceBuilder{
+	pub fn query(client: &Client) -> Query{
+		Query{
+			client: client.clone(),
+			state: None,
+			from_time: None,
+			to_time: None,
+			size: None,
+			sort_order: None
+		}
+	}
+	pub fn build<N: Into<PhoneNumber>>(client: &Client, from: N) -> ConferenceBuilder{
 		ConferenceBuilder{
 			client: client.clone(),
-			from: from.to_owned(),
+			from: from.into().get_e164(),
 			callback_url: None,
 			callback_http_method: "POST".to_owned(),
 			callback_timeout: None,
@@ -228,6 +342,27 @@ impl Conference{
 	pub fn list_members(&self) -> CatapultResult<Vec<Member>>{
 		Member::list_members_from_conference(self)
 	}
+	///Fetches a single member, failing if it isn't part of this conference
+	pub fn get_member(&self, id: &str) -> CatapultResult<Member>{
+		let member = Member::get(self, id);
+		try!(member.load());
+		Ok(member)
+	}
+	///The member that `call_id` joined as, if any.
+	///A call that left and rejoined has several members, the active one is preferred
+	pub fn find_member_by_call(&self, call_id: &str) -> CatapultResult<Option<Member>>{
+		let mut found = None;
+		for member in try!(self.list_members()){
+			if try!(member.get_call_id()) != call_id{
+				continue;
+			}
+			if let member::State::Active = try!(member.get_state()){
+				return Ok(Some(member))
+			}
+			found = Some(member);
+		}
+		Ok(found)
+	}
 	
 	pub fn get_active_members(&self) -> CatapultResult<u64>{
 		if !self.data.lock().unwrap().active_members.available(){
@@ -314,28 +449,19 @@ impl Conference{
 		Ok(())
 	}
 	
+	fn audio_path(&self) -> String{
+		"users/".to_string() + &self.client.get_user_id() + "/conferences/" + &self.get_id() + "/audio"
+	}
 	pub fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
-		let user_id = self.client.get_user_id();
-		let path = "users/".to_string() + &user_id + "/conferences/" + &self.get_id() + "/audio";
-		let json = json!({
-			"sentence" => (sentence),
-			"loopEnabled" => (loop_audio),
-			"voice" => (voice.get_name()),
-			"tag" => (tag.map(|a|a.to_string()))
-		});
-		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::speak_sentence(&self.client, &self.audio_path(), sentence, loop_audio, voice, tag)
 	}
 	pub fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
-		let user_id = self.client.get_user_id();
-		let path = "users/".to_string() + &user_id + "/conferences/" + &self.get_id() + "/audio";
-		let json = json!({
-			"fileUrl" => (url),
-			"loopEnabled" => (loop_audio),
-			"tag" => (tag.map(|a|a.to_string()))
-		});
-		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
-		Ok(())
+		audio::play_audio_file(&self.client, &self.audio_path(), url, loop_audio, tag)
+	}
+	///Stops either an audio file playing, or a sentence being spoken
+	///This is the only way to stop audio in a loop
+	pub fn stop_audio(&self) -> CatapultResult<()>{
+		audio::stop_audio(&self.client, &self.audio_path())
 	}
 	
 	/* Setters */
@@ -365,3 +491,14 @@ impl Conference{
 	}
 }
 
+impl AudioTarget for Conference{
+	fn play_audio_file(&self, url: &str, loop_audio: bool, tag: Option<&str>) -> CatapultResult<()>{
+		Conference::play_audio_file(self, url, loop_audio, tag)
+	}
+	fn speak_sentence(&self, sentence: &str, loop_audio: bool, voice: Voice, tag: Option<&str>) -> CatapultResult<()>{
+		Conference::speak_sentence(self, sentence, loop_audio, voice, tag)
+	}
+	fn stop_audio(&self) -> CatapultResult<()>{
+		Conference::stop_audio(self)
+	}
+}
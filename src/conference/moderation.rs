@@ -0,0 +1,215 @@
+use {CatapultResult, CatapultError};
+use conference::{Conference, Member, MemberBuilder, MemberState};
+use conference_event::{ConferenceEvent, EventType};
+use std::sync::{Arc, Mutex};
+use std::collections::HashSet;
+use std::thread;
+
+///Keeps a large conference from opening hundreds of connections at once
+const MAX_CONCURRENT_UPDATES: usize = 10;
+
+///The outcome of updating many members at once
+pub struct ModerationReport{
+	updated: Vec<Member>,
+	failures: Vec<(Member, CatapultError)>
+}
+impl ModerationReport{
+	pub fn is_success(&self) -> bool{
+		self.failures.is_empty()
+	}
+	pub fn get_updated(&self) -> &Vec<Member>{
+		&self.updated
+	}
+	///Members that kept their previous settings
+	pub fn get_failures(&self) -> &Vec<(Member, CatapultError)>{
+		&self.failures
+	}
+}
+
+fn update_members<F>(members: Vec<Member>, update: F) -> ModerationReport
+where F: Fn(&Member) -> CatapultResult<()> + Send + Sync + 'static{
+	let update = Arc::new(update);
+	let mut report = ModerationReport{
+		updated: vec!(),
+		failures: vec!()
+	};
+	let mut members = members.into_iter().peekable();
+	while members.peek().is_some(){
+		let workers: Vec<_> = members.by_ref().take(MAX_CONCURRENT_UPDATES).map(|member|{
+			let update = update.clone();
+			let worker_member = member.clone();
+			(member, thread::spawn(move || update(&worker_member)))
+		}).collect();
+		for (member, worker) in workers{
+			match worker.join(){
+				Ok(Ok(())) => report.updated.push(member),
+				Ok(Err(err)) => report.failures.push((member, err)),
+				Err(_) => report.failures.push((member, CatapultError::unexpected("member update panicked")))
+			}
+		}
+	}
+	report
+}
+
+impl Conference{
+	fn list_active_members(&self) -> CatapultResult<Vec<Member>>{
+		let mut output = vec!();
+		for member in try!(self.list_members()){
+			if let MemberState::Active = try!(member.get_state()){
+				output.push(member);
+			}
+		}
+		Ok(output)
+	}
+	///Mutes every active member except `member_id`, who is unmuted
+	pub fn mute_all_except(&self, member_id: &str) -> CatapultResult<ModerationReport>{
+		let member_id = member_id.to_owned();
+		Ok(update_members(try!(self.list_active_members()), move |member|{
+			member.set_mute(member.get_id() != member_id);
+			member.save()
+		}))
+	}
+	pub fn unmute_all(&self) -> CatapultResult<ModerationReport>{
+		Ok(update_members(try!(self.list_active_members()), |member|{
+			member.set_mute(false);
+			member.save()
+		}))
+	}
+	pub fn hold_members(&self, members: &[Member]) -> ModerationReport{
+		update_members(members.to_vec(), |member|{
+			member.set_hold(true);
+			member.save()
+		})
+	}
+	pub fn unhold_members(&self, members: &[Member]) -> ModerationReport{
+		update_members(members.to_vec(), |member|{
+			member.set_hold(false);
+			member.save()
+		})
+	}
+	///Removes every active member. The conference itself stays open, use end() to close it
+	pub fn remove_all(&self) -> CatapultResult<ModerationReport>{
+		Ok(update_members(try!(self.list_active_members()), |member| member.remove()))
+	}
+}
+
+///Locks a conference and runs lecture mode. Catapult has neither, so the state lives in
+///this Moderator: it is lost when the process exits, and other Moderators, clients, or
+///processes don't see it. Members added through build_member() get the current settings
+///before they join. Anyone else is only caught after joining, when their event is
+///passed to handle_event(), so they may hear a moment of the conference and the
+///join tone will already have played. handle_event() only acts on the first event for each
+///member, and members already present at lock() or start_lecture() count as seen
+#[derive(Clone)]
+pub struct Moderator{
+	conf: Conference,
+	data: Arc<Mutex<Data>>
+}
+struct Data{
+	locked: bool,
+	presenter_id: Option<String>,
+	known_members: HashSet<String>
+}
+impl Moderator{
+	pub fn new(conf: &Conference) -> Moderator{
+		Moderator{
+			conf: conf.clone(),
+			data: Arc::new(Mutex::new(Data{
+				locked: false,
+				presenter_id: None,
+				known_members: HashSet::new()
+			}))
+		}
+	}
+	pub fn get_conference(&self) -> Conference{
+		self.conf.clone()
+	}
+	///build_member() is refused and handle_event() removes members that join.
+	///Current members stay
+	pub fn lock(&self) -> CatapultResult<()>{
+		let members = try!(self.conf.list_active_members());
+		let mut data = self.data.lock().unwrap();
+		data.known_members.extend(members.iter().map(|member| member.get_id()));
+		data.locked = true;
+		Ok(())
+	}
+	pub fn unlock(&self){
+		self.data.lock().unwrap().locked = false;
+	}
+	pub fn is_locked(&self) -> bool{
+		self.data.lock().unwrap().locked
+	}
+	///Only `presenter_id` can be heard. Members on hold are taken off hold so they can listen,
+	///and leave tones are turned off. New members join muted without a join tone
+	pub fn start_lecture(&self, presenter_id: &str) -> CatapultResult<ModerationReport>{
+		let members = try!(self.conf.list_active_members());
+		{
+			let mut data = self.data.lock().unwrap();
+			data.known_members.extend(members.iter().map(|member| member.get_id()));
+			data.presenter_id = Some(presenter_id.to_owned());
+		}
+		let presenter_id = presenter_id.to_owned();
+		Ok(update_members(members, move |member|{
+			member.set_mute(member.get_id() != presenter_id);
+			member.set_hold(false);
+			member.leaving_tone(false);
+			member.save()
+		}))
+	}
+	///New members join with the defaults again. Current members keep their settings, see unmute_all()
+	pub fn end_lecture(&self){
+		self.data.lock().unwrap().presenter_id = None;
+	}
+	pub fn get_presenter_id(&self) -> Option<String>{
+		self.data.lock().unwrap().presenter_id.clone()
+	}
+	///Like Conference::build_member(), with the lecture settings applied.
+	///Fails if the conference is locked
+	pub fn build_member(&self, call_id: &str) -> CatapultResult<MemberBuilder>{
+		let data = self.data.lock().unwrap();
+		if data.locked{
+			return Err(CatapultError::bad_input("conference is locked"))
+		}
+		let builder = self.conf.build_member(call_id);
+		Ok(match data.presenter_id{
+			Some(_) => builder.mute().no_join_tone().no_leaving_tone(),
+			None => builder
+		})
+	}
+	///Applies the lock and lecture settings to a member that just joined.
+	///Events for other conferences, other event types, and members seen before are ignored
+	pub fn handle_event(&self, event: &ConferenceEvent) -> CatapultResult<()>{
+		if event.get_conference_id() != self.conf.get_id(){
+			return Ok(())
+		}
+		let joined = match *event.get_event_type(){
+			EventType::Member(ref member_event) => match member_event.get_state(){
+				MemberState::Active => true,
+				MemberState::Completed => false
+			},
+			_ => false
+		};
+		let member = match event.get_member(){
+			Some(ref member) if joined => member.clone(),
+			_ => return Ok(())
+		};
+		let (locked, presenter_id) = {
+			let mut data = self.data.lock().unwrap();
+			if !data.known_members.insert(member.get_id()){
+				return Ok(())
+			}
+			(data.locked, data.presenter_id.clone())
+		};
+		if locked{
+			return member.remove()
+		}
+		match presenter_id{
+			Some(ref presenter_id) if *presenter_id != member.get_id() => {
+				member.set_mute(true);
+				member.leaving_tone(false);
+				member.save()
+			},
+			_ => Ok(())
+		}
+	}
+}
@@ -0,0 +1,191 @@
+use {CatapultResult, CatapultError, Client};
+use rustc_serialize::json;
+use conference::{Conference, Member, MemberState, State};
+use self::info::ConferenceEventInfo;
+use util;
+
+///A callback posted to a conference's callback url
+pub struct ConferenceEvent{
+	client: Client,
+	event_type: EventType,
+	conference_id: String,
+	member_id: Option<String>,
+	time: Option<String>
+}
+
+pub enum EventType{
+	///The conference was created or completed
+	State(StateEvent),
+	///A member joined, left, or was muted or put on hold
+	Member(MemberEvent),
+	///Audio played to the conference, or to a single member if get_member_id() is set
+	Playback(AudioEvent),
+	///A sentence spoken to the conference, or to a single member if get_member_id() is set
+	Speak(AudioEvent)
+}
+
+#[derive(Clone, Debug)]
+pub enum Status{
+	Started,
+	Done
+}
+
+pub struct StateEvent{
+	state: State,
+	active_members: Option<u64>,
+	created_time: Option<String>,
+	completed_time: Option<String>
+}
+impl StateEvent{
+	fn new(info: &ConferenceEventInfo) -> CatapultResult<StateEvent>{
+		let status = try!(util::expect(info.status.clone(), "ConferenceEvent::status"));
+		Ok(StateEvent{
+			state: try!(State::parse(&status)),
+			active_members: info.activeMembers,
+			created_time: info.createdTime.clone(),
+			completed_time: info.completedTime.clone()
+		})
+	}
+	pub fn get_state(&self) -> State{
+		self.state.clone()
+	}
+	pub fn get_active_members(&self) -> Option<u64>{
+		self.active_members
+	}
+	pub fn get_created_time(&self) -> Option<String>{
+		self.created_time.clone()
+	}
+	pub fn get_completed_time(&self) -> Option<String>{
+		self.completed_time.clone()
+	}
+}
+
+pub struct MemberEvent{
+	call_id: String,
+	state: MemberState,
+	active_members: Option<u64>,
+	hold: Option<bool>,
+	mute: Option<bool>
+}
+impl MemberEvent{
+	fn new(info: &ConferenceEventInfo) -> CatapultResult<MemberEvent>{
+		let state = try!(util::expect(info.state.clone(), "ConferenceEvent::state"));
+		Ok(MemberEvent{
+			call_id: try!(util::expect(info.callId.clone(), "ConferenceEvent::callId")),
+			state: try!(MemberState::parse(&state)),
+			active_members: info.activeMembers,
+			hold: info.hold,
+			mute: info.mute
+		})
+	}
+	pub fn get_call_id(&self) -> String{
+		self.call_id.clone()
+	}
+	///Active when the member joined, Completed when it left
+	pub fn get_state(&self) -> MemberState{
+		self.state.clone()
+	}
+	pub fn get_active_members(&self) -> Option<u64>{
+		self.active_members
+	}
+	pub fn get_hold(&self) -> Option<bool>{
+		self.hold
+	}
+	pub fn get_mute(&self) -> Option<bool>{
+		self.mute
+	}
+}
+
+pub struct AudioEvent{
+	status: Status,
+	tag: Option<String>
+}
+impl AudioEvent{
+	fn new(info: &ConferenceEventInfo) -> CatapultResult<AudioEvent>{
+		let status = try!(util::expect(info.status.clone(), "ConferenceEvent::status"));
+		Ok(AudioEvent{
+			status: match status.as_ref(){
+				"started" => Status::Started,
+				"done" => Status::Done,
+				status @ _ => return Err(CatapultError::unexpected(
+					&format!("unknown ConferenceEvent audio status: {}", status)
+				))
+			},
+			tag: info.tag.clone()
+		})
+	}
+	pub fn get_status(&self) -> Status{
+		self.status.clone()
+	}
+	pub fn get_tag(&self) -> Option<String>{
+		self.tag.clone()
+	}
+}
+
+mod info{
+	#![allow(non_snake_case)]
+	#[derive(RustcDecodable)]
+	pub struct ConferenceEventInfo{
+		pub eventType: String,
+		pub conferenceId: String,
+		pub memberId: Option<String>,
+		pub callId: Option<String>,
+		pub status: Option<String>,
+		pub state: Option<String>,
+		pub activeMembers: Option<u64>,
+		pub createdTime: Option<String>,
+		pub completedTime: Option<String>,
+		pub hold: Option<bool>,
+		pub mute: Option<bool>,
+		pub tag: Option<String>,
+		pub time: Option<String>
+	}
+}
+
+impl ConferenceEvent{
+	pub fn parse(client: &Client, data: &str) -> CatapultResult<ConferenceEvent>{
+		let info: ConferenceEventInfo = try!(json::decode(data));
+		let event_type = match info.eventType.as_ref(){
+			"conference" => EventType::State(try!(StateEvent::new(&info))),
+			"conference-member" => EventType::Member(try!(MemberEvent::new(&info))),
+			"conference-playback" => EventType::Playback(try!(AudioEvent::new(&info))),
+			"conference-speak" => EventType::Speak(try!(AudioEvent::new(&info))),
+			event @ _ => return Err(CatapultError::unexpected(&format!("unknown conference event: {}", event)))
+		};
+		if let EventType::Member(_) = event_type{
+			try!(util::expect(info.memberId.as_ref(), "ConferenceEvent::memberId"));
+		}
+		Ok(ConferenceEvent{
+			client: client.clone(),
+			event_type: event_type,
+			conference_id: info.conferenceId.clone(),
+			member_id: info.memberId.clone(),
+			time: info.time.clone()
+		})
+	}
+	
+	pub fn get_client(&self) -> Client{
+		self.client.clone()
+	}
+	pub fn get_event_type(&self) -> &EventType{
+		&self.event_type
+	}
+	pub fn get_conference_id(&self) -> String{
+		self.conference_id.clone()
+	}
+	pub fn get_conference(&self) -> Conference{
+		Conference::get(&self.client, &self.conference_id)
+	}
+	///Set for member events, and for audio played or spoken to a single member
+	pub fn get_member_id(&self) -> Option<String>{
+		self.member_id.clone()
+	}
+	pub fn get_member(&self) -> Option<Member>{
+		self.member_id.as_ref().map(|id|{
+			Member::get(&self.get_conference(), id)
+		})
+	}
+	pub fn get_time(&self) -> Option<String>{
+		self.time.clone()
+	}
+}
@@ -0,0 +1,155 @@
+use {CatapultResult, Client, PhoneNumber};
+use message::{self, Message, PendingMessage, QueryResult};
+use std::collections::VecDeque;
+
+///All messages exchanged between one of our numbers and another number
+pub struct Conversation{
+	client: Client,
+	our_number: PhoneNumber,
+	their_number: PhoneNumber
+}
+impl Conversation{
+	pub fn new<O, T>(client: &Client, our_number: O, their_number: T) -> Conversation
+	where O: Into<PhoneNumber>, T: Into<PhoneNumber>{
+		Conversation{
+			client: client.clone(),
+			our_number: our_number.into(),
+			their_number: their_number.into()
+		}
+	}
+
+	///Messages in both directions, oldest first. Pages are fetched as needed.
+	pub fn history(&self) -> History{
+		self.build_history(false)
+	}
+	///Messages in both directions, newest first. Pages are fetched as needed.
+	pub fn recent_history(&self) -> History{
+		self.build_history(true)
+	}
+	fn build_history(&self, descending: bool) -> History{
+		let sent = Message::query(&self.client)
+			.from_number(&self.our_number)
+			.to_number(&self.their_number);
+		let received = Message::query(&self.client)
+			.from_number(&self.their_number)
+			.to_number(&self.our_number);
+		History{
+			sent: Side::new(if descending {sent.desc()} else {sent}),
+			received: Side::new(if descending {received.desc()} else {received}),
+			descending: descending
+		}
+	}
+
+	///A message from our number to theirs
+	pub fn build_reply(&self, text: &str) -> PendingMessage{
+		Message::build(&self.client, &self.our_number, &self.their_number, text)
+	}
+
+	/* Getters */
+	pub fn get_client(&self) -> Client{
+		self.client.clone()
+	}
+	pub fn get_our_number(&self) -> PhoneNumber{
+		self.our_number.clone()
+	}
+	pub fn get_their_number(&self) -> PhoneNumber{
+		self.their_number.clone()
+	}
+}
+
+struct Side{
+	query: Option<message::Query>,
+	result: Option<QueryResult>,
+	page: VecDeque<Message>
+}
+impl Side{
+	fn new(query: message::Query) -> Side{
+		Side{
+			query: Some(query),
+			result: None,
+			page: VecDeque::new()
+		}
+	}
+	fn fetch(&mut self) -> CatapultResult<()>{
+		while self.page.is_empty(){
+			let result = match self.query.take(){
+				Some(query) => try!(query.submit()),
+				None => match self.result.take().and_then(|result| result.next()){
+					Some(result) => try!(result),
+					None => return Ok(())
+				}
+			};
+			self.page.extend(result.get_messages().iter().cloned());
+			self.result = Some(result);
+		}
+		Ok(())
+	}
+	fn peek_time(&mut self) -> CatapultResult<Option<(String, String)>>{
+		try!(self.fetch());
+		Ok(match self.page.front(){
+			Some(msg) => Some(time_key(&try!(msg.get_time()))),
+			None => None
+		})
+	}
+}
+
+///Splits an ISO-8601 UTC time into whole seconds and a fraction padded to nanoseconds,
+///so 12:00:09Z sorts before 12:00:09.5Z
+fn time_key(time: &str) -> (String, String){
+	let time = time.trim_end_matches('Z');
+	let (seconds, fraction) = match time.find('.'){
+		Some(index) => (&time[..index], &time[index + 1..]),
+		None => (time, "")
+	};
+	(seconds.to_owned(), format!("{:0<9}", fraction))
+}
+
+///Iterates over both directions of a Conversation, merged in time order
+pub struct History{
+	sent: Side,
+	received: Side,
+	descending: bool
+}
+impl Iterator for History{
+	type Item = CatapultResult<Message>;
+	fn next(&mut self) -> Option<CatapultResult<Message>>{
+		let sent_time = match self.sent.peek_time(){
+			Ok(time) => time,
+			Err(err) => return Some(Err(err))
+		};
+		let received_time = match self.received.peek_time(){
+			Ok(time) => time,
+			Err(err) => return Some(Err(err))
+		};
+		let use_sent = match (sent_time, received_time){
+			(Some(sent), Some(received)) => (sent <= received) != self.descending,
+			(Some(_), None) => true,
+			(None, Some(_)) => false,
+			(None, None) => return None
+		};
+		let side = if use_sent {&mut self.sent} else {&mut self.received};
+		side.page.pop_front().map(|msg| Ok(msg))
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use super::time_key;
+
+	#[test]
+	fn whole_seconds_sort_before_fractions_of_the_same_second(){
+		assert!(time_key("2016-01-01T12:00:09Z") < time_key("2016-01-01T12:00:09.5Z"));
+		assert!(time_key("2016-01-01T12:00:09.999Z") < time_key("2016-01-01T12:00:10Z"));
+	}
+	#[test]
+	fn fractions_of_different_lengths(){
+		assert!(time_key("2016-01-01T12:00:09.5Z") > time_key("2016-01-01T12:00:09.25Z"));
+		assert!(time_key("2016-01-01T12:00:09.05Z") < time_key("2016-01-01T12:00:09.5Z"));
+		assert_eq!(time_key("2016-01-01T12:00:09.5Z"), time_key("2016-01-01T12:00:09.500Z"));
+		assert_eq!(time_key("2016-01-01T12:00:09Z"), time_key("2016-01-01T12:00:09.000Z"));
+	}
+	#[test]
+	fn missing_z_suffix(){
+		assert_eq!(time_key("2016-01-01T12:00:09.5"), time_key("2016-01-01T12:00:09.5Z"));
+	}
+}
@@ -0,0 +1,243 @@
+use {CatapultResult, CatapultError, Domain, Endpoint};
+use std::collections::{BTreeMap, BTreeSet};
+
+///How an endpoint should look after syncing. Endpoints are matched by name
+#[derive(Clone, Debug)]
+pub struct DesiredEndpoint{
+	name: String,
+	application_id: String,
+	description: Option<String>,
+	enabled: bool,
+	password: Option<String>
+}
+impl DesiredEndpoint{
+	pub fn new(name: &str, application_id: &str) -> DesiredEndpoint{
+		DesiredEndpoint{
+			name: name.to_owned(),
+			application_id: application_id.to_owned(),
+			description: None,
+			enabled: true,
+			password: None
+		}
+	}
+	pub fn description(mut self, desc: &str) -> Self{
+		self.description = Some(desc.to_owned()); self
+	}
+	pub fn disable(mut self) -> Self{
+		self.enabled = false; self
+	}
+	///Required to create the endpoint. Passwords can't be read back, so existing endpoints
+	///only get it when DirectorySync::update_passwords() is used
+	pub fn password(mut self, password: &str) -> Self{
+		self.password = Some(password.to_owned()); self
+	}
+	
+	/* Getters */
+	pub fn get_name(&self) -> String{
+		self.name.clone()
+	}
+	pub fn get_application_id(&self) -> String{
+		self.application_id.clone()
+	}
+	pub fn get_description(&self) -> Option<String>{
+		self.description.clone()
+	}
+	pub fn get_enabled(&self) -> bool{
+		self.enabled
+	}
+}
+
+#[derive(Clone)]
+pub enum SyncChange{
+	Create(DesiredEndpoint),
+	///`fields` are the names of the fields that differ, eg: "description"
+	Update{
+		endpoint: Endpoint,
+		desired: DesiredEndpoint,
+		fields: Vec<&'static str>
+	},
+	Delete(Endpoint)
+}
+impl SyncChange{
+	///The name of the endpoint being changed
+	pub fn get_name(&self) -> CatapultResult<String>{
+		match *self{
+			SyncChange::Create(ref desired) => Ok(desired.get_name()),
+			SyncChange::Update{ref desired, ..} => Ok(desired.get_name()),
+			SyncChange::Delete(ref endpoint) => endpoint.get_name()
+		}
+	}
+	fn apply(&self, domain: &Domain) -> CatapultResult<()>{
+		match *self{
+			SyncChange::Create(ref desired) => {
+				let password = try!(desired.password.as_ref().ok_or(CatapultError::bad_input(
+					&format!("endpoint {} needs a password to be created", desired.name)
+				)));
+				let mut builder = domain.build_endpoint(&desired.application_id, &desired.name, password);
+				if let Some(ref description) = desired.description{
+					builder = builder.description(description);
+				}
+				if !desired.enabled{
+					builder = builder.disable();
+				}
+				try!(builder.create());
+				Ok(())
+			},
+			SyncChange::Update{ref endpoint, ref desired, ..} => {
+				endpoint.set_description(desired.description.as_ref().map(|a| a.as_ref()));
+				endpoint.set_enabled(desired.enabled);
+				endpoint.set_application_id(&desired.application_id);
+				if let Some(ref password) = desired.password{
+					endpoint.set_password(password);
+				}
+				endpoint.save()
+			},
+			SyncChange::Delete(ref endpoint) => endpoint.delete()
+		}
+	}
+}
+
+///Makes a domain's endpoints match a list kept elsewhere, such as your own user directory
+pub struct DirectorySync{
+	domain: Domain,
+	desired: Vec<DesiredEndpoint>,
+	delete_extra: bool,
+	update_passwords: bool
+}
+impl DirectorySync{
+	pub fn new(domain: &Domain, desired: Vec<DesiredEndpoint>) -> DirectorySync{
+		DirectorySync{
+			domain: domain.clone(),
+			desired: desired,
+			delete_extra: true,
+			update_passwords: false
+		}
+	}
+	///Endpoints missing from the desired list are left alone instead of deleted
+	pub fn keep_extra(mut self) -> Self{
+		self.delete_extra = false; self
+	}
+	///Sends the password of every existing endpoint that has one in the desired list
+	pub fn update_passwords(mut self) -> Self{
+		self.update_passwords = true; self
+	}
+	
+	///Works out what would change without changing anything (a dry run)
+	pub fn plan(&self) -> CatapultResult<SyncPlan>{
+		let mut desired = BTreeMap::new();
+		for endpoint in self.desired.iter(){
+			if desired.insert(endpoint.name.clone(), endpoint).is_some(){
+				return Err(CatapultError::bad_input(&format!("duplicate endpoint name: {}", endpoint.name)))
+			}
+		}
+		let mut existing_names = BTreeSet::new();
+		let mut updates = vec!();
+		let mut deletes = vec!();
+		let mut page = try!(self.domain.list_endpoints().submit());
+		loop{
+			for endpoint in page.get_endpoints(){
+				let name = try!(endpoint.get_name());
+				match desired.get(&name){
+					Some(wanted) => {
+						let fields = try!(self.changed_fields(endpoint, wanted));
+						if !fields.is_empty(){
+							let mut wanted = (*wanted).clone();
+							if !self.update_passwords{
+								wanted.password = None;
+							}
+							updates.push(SyncChange::Update{
+								endpoint: endpoint.clone(),
+								desired: wanted,
+								fields: fields
+							});
+						}
+					},
+					None => if self.delete_extra{
+						deletes.push(SyncChange::Delete(endpoint.clone()));
+					}
+				}
+				existing_names.insert(name);
+			}
+			page = match page.next(){
+				Some(next) => try!(next),
+				None => break
+			};
+		}
+		//creates first and deletes last, so a failure part way leaves as many users working as possible
+		let mut changes: Vec<SyncChange> = desired.into_iter()
+			.filter(|&(ref name, _)| !existing_names.contains(name))
+			.map(|(_, wanted)| SyncChange::Create(wanted.clone()))
+			.collect();
+		changes.extend(updates);
+		changes.extend(deletes);
+		Ok(SyncPlan{
+			domain: self.domain.clone(),
+			changes: changes
+		})
+	}
+	fn changed_fields(&self, endpoint: &Endpoint, wanted: &DesiredEndpoint) -> CatapultResult<Vec<&'static str>>{
+		let mut fields = vec!();
+		if try!(endpoint.get_description()) != wanted.description{
+			fields.push("description");
+		}
+		if try!(endpoint.get_enabled()) != wanted.enabled{
+			fields.push("enabled");
+		}
+		if try!(endpoint.get_application_id()) != wanted.application_id{
+			fields.push("applicationId");
+		}
+		if self.update_passwords && wanted.password.is_some(){
+			fields.push("password");
+		}
+		Ok(fields)
+	}
+	///Plans and applies the changes in one step
+	pub fn sync(&self) -> CatapultResult<SyncReport>{
+		Ok(try!(self.plan()).apply())
+	}
+}
+
+///The changes a sync would make. Nothing is changed until apply() is called
+pub struct SyncPlan{
+	domain: Domain,
+	changes: Vec<SyncChange>
+}
+impl SyncPlan{
+	pub fn get_changes(&self) -> &Vec<SyncChange>{
+		&self.changes
+	}
+	///True if the domain already matches
+	pub fn is_empty(&self) -> bool{
+		self.changes.is_empty()
+	}
+	///Applies every change, carrying on past failures
+	pub fn apply(self) -> SyncReport{
+		let mut report = SyncReport{
+			applied: vec!(),
+			failures: vec!()
+		};
+		for change in self.changes{
+			match change.apply(&self.domain){
+				Ok(()) => report.applied.push(change),
+				Err(err) => report.failures.push((change, err))
+			}
+		}
+		report
+	}
+}
+
+pub struct SyncReport{
+	applied: Vec<SyncChange>,
+	failures: Vec<(SyncChange, CatapultError)>
+}
+impl SyncReport{
+	pub fn is_success(&self) -> bool{
+		self.failures.is_empty()
+	}
+	pub fn get_applied(&self) -> &Vec<SyncChange>{
+		&self.applied
+	}
+	pub fn get_failures(&self) -> &Vec<(SyncChange, CatapultError)>{
+		&self.failures
+	}
+}
@@ -5,7 +5,9 @@ use lazy::Lazy;
 use util;
 use error::CatapultError;
 use endpoint;
+use directory_sync::{DesiredEndpoint, DirectorySync};
 
+#[derive(Clone)]
 pub struct Domain{
 	id: String,
 	client: Client,
@@ -85,6 +87,11 @@ impl Domain{
 		}
 		Ok(output)
 	}
+	pub fn delete(&self) -> CatapultResult<()>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/domains/" + &self.id;
+		let _:EmptyResponse = try!(self.client.raw_delete_request(&path, ()));
+		Ok(())
+	}
 	pub fn get_client(&self) -> Client{
 		self.client.clone()
 	}
@@ -97,10 +104,31 @@ impl Domain{
 	pub fn get_endpoint_by_id(&self, id: &str) -> Endpoint{
 		Endpoint::get_by_id(&self.client, id, &self.id)
 	}
+	///Searches every page of endpoints for the one with this SIP username
+	pub fn get_endpoint_by_username(&self, username: &str) -> CatapultResult<Option<Endpoint>>{
+		let mut page = try!(self.list_endpoints().submit());
+		loop{
+			for endpoint in page.get_endpoints(){
+				if try!(endpoint.get_username()) == username{
+					return Ok(Some(endpoint.clone()))
+				}
+			}
+			page = match page.next(){
+				Some(next) => try!(next),
+				None => return Ok(None)
+			};
+		}
+	}
+	pub fn list_endpoints(&self) -> endpoint::Query{
+		Endpoint::query(&self.client, &self.id)
+	}
 	
 	/* Object Helpers */
 	//Endpoint
 	pub fn build_endpoint(&self, app_id: &str, name: &str, password: &str) -> endpoint::EndpointBuilder{
 		Endpoint::build(&self.client, &self.id, app_id, name, password)
 	}
+	pub fn build_sync(&self, desired: Vec<DesiredEndpoint>) -> DirectorySync{
+		DirectorySync::new(self, desired)
+	}
 }
\ No newline at end of file
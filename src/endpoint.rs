@@ -7,7 +7,7 @@ use lazy::Lazy;
 use lazy::Lazy::*;
 use util;
 use std::collections::BTreeMap;
-use rustc_serialize::json::{Json, ToJson};
+use rustc_serialize::json::{self, Json, ToJson};
 use self::info::EndpointInfo;
 
 
@@ -22,6 +22,20 @@ struct Data{
 	sip_uri: Lazy<String>,
 	password: Lazy<String> //write only
 }
+impl Data{
+	fn from_info(info: EndpointInfo) -> Data{
+		Data{
+			name: Available(info.name),
+			description: Available(info.description),
+			enabled: Available(info.enabled),
+			application_id: Available(info.applicationId),
+			realm: Available(info.credentials.realm),
+			username: Available(info.credentials.username),
+			sip_uri: Available(info.sipUri),
+			password: NotLoaded
+		}
+	}
+}
 
 mod info{
 	#![allow(non_snake_case)]
@@ -91,6 +105,68 @@ impl EndpointBuilder{
 	}
 }
 
+fn get_endpoint_list<P: json::ToJson>(client: &Client, domain_id: &str, path: &str, params: P) -> CatapultResult<QueryResult>{
+	let res:JsonResponse<Vec<EndpointInfo>> = try!(client.raw_get_request(&path, params, ()));
+	let mut output = vec!();
+	for info in res.body{
+		output.push(Endpoint{
+			id: info.id.clone(),
+			domain_id: domain_id.to_owned(),
+			client: client.clone(),
+			data: Arc::new(Mutex::new(Data::from_info(info)))
+		});
+	}
+	let next_url = try!(util::get_next_link_from_headers(&res.headers));
+	Ok(QueryResult{
+		client: client.clone(),
+		domain_id: domain_id.to_owned(),
+		data: output,
+		next_url: next_url
+	})
+}
+pub struct QueryResult{
+	client: Client,
+	domain_id: String,
+	data: Vec<Endpoint>,
+	next_url: Option<String>
+}
+impl QueryResult{
+	pub fn get_endpoints(&self) -> &Vec<Endpoint>{
+		&self.data
+	}
+	pub fn has_next(&self) -> bool{
+		self.next_url.is_some()
+	}
+	pub fn next(&self) -> Option<CatapultResult<QueryResult>>{
+		self.next_url.as_ref().map(|ref url|{
+			get_endpoint_list(&self.client, &self.domain_id, &url, ())
+		})
+	}
+}
+
+pub struct Query{
+	client: Client,
+	domain_id: String,
+	size: Option<u32>
+}
+impl Query{
+	pub fn size(mut self, size: u32) -> Query{
+		self.size = Some(size); self
+	}
+	pub fn submit(self) -> CatapultResult<QueryResult>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/domains/" + &self.domain_id + "/endpoints";
+		
+		let mut map = BTreeMap::new();
+		if let Some(size) = self.size{
+			map.insert("size".to_string(), size.to_json());
+		}
+		let json = Json::Object(map);
+		
+		get_endpoint_list(&self.client, &self.domain_id, &path, json)
+	}
+}
+
+#[derive(Clone)]
 pub struct Endpoint{
 	id: String,
 	domain_id: String,
@@ -102,13 +178,10 @@ impl Endpoint{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/domains/" + &self.domain_id + "/endpoints/" + &self.id;
 		let res:JsonResponse<EndpointInfo> = try!(self.client.raw_get_request(&path, (), ()));
 		let mut data = self.data.lock().unwrap();
-		data.name = Available(res.body.name);
-		data.description = Available(res.body.description);
-		data.enabled = Available(res.body.enabled);
-		data.application_id= Available(res.body.applicationId);
-		data.realm = Available(res.body.credentials.realm);
-		data.username = Available(res.body.credentials.username);
-		data.sip_uri = Available(res.body.sipUri);
+		let password = data.password.peek().cloned();
+		*data = Data::from_info(res.body);
+		//write only, keep it so an unsaved password isn't lost
+		data.password = Lazy::load_if_available(password);
 		Ok(())
 	}
 	pub fn save(&self) -> CatapultResult<()>{
@@ -150,6 +223,13 @@ impl Endpoint{
 			}))
 		}
 	}
+	pub fn query(client: &Client, domain_id: &str) -> Query{
+		Query{
+			client: client.clone(),
+			domain_id: domain_id.to_owned(),
+			size: None
+		}
+	}
 	pub fn build(client: &Client, domain_id: &str, app_id: &str, name: &str, password: &str) -> EndpointBuilder{
 		EndpointBuilder{
 			client: client.clone(),
@@ -164,6 +244,11 @@ impl Endpoint{
 	pub fn create_auth_token(&self) -> CatapultResult<AuthToken>{
 		AuthToken::create(self)
 	}
+	pub fn delete(&self) -> CatapultResult<()>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/domains/" + &self.domain_id + "/endpoints/" + &self.id;
+		let _:EmptyResponse = try!(self.client.raw_delete_request(&path, ()));
+		Ok(())
+	}
 	
 	/* Getters */
 	pub fn get_id(&self) -> String{
@@ -17,6 +17,8 @@ pub enum CatapultError{
 	InternalError(String),
 	Unexpected(String),
 	BadInput(String),
+	///the recipient number has opted out of receiving messages
+	OptedOut(String),
 	Utf8Error
 }
 
@@ -82,4 +84,7 @@ impl CatapultError{
 	pub fn bad_input(msg: &str) -> CatapultError{
 		CatapultError::BadInput(msg.to_owned())
 	}
+	pub fn opted_out(number: &str) -> CatapultError{
+		CatapultError::OptedOut(number.to_owned())
+	}
 }
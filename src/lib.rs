@@ -28,22 +28,31 @@ macro_rules! lazy_load {
 
 pub mod account;
 pub mod application;
+pub mod bridge;
 pub mod call;
 pub mod call_event;
 pub mod client;
+pub mod compliance;
 pub mod conference;
+pub mod conference_event;
+pub mod conversation;
+pub mod directory_sync;
 pub mod endpoint;
 pub mod error;
+pub mod media;
 pub mod message;
 pub mod message_event;
 pub mod number;
+pub mod number_info;
+pub mod phone_number;
+pub mod provisioning;
+pub mod scheduler;
 
+mod audio;
 mod auth_token;
-mod bridge;
 mod domain;
 mod environment;
 mod lazy;
-mod media;
 mod util;
 mod voice;
 
@@ -52,24 +61,32 @@ pub type CatapultResult<T> = Result<T, error::CatapultError>;
 
 pub use account::Account;
 pub use application::Application;
-pub use auth_token::AuthToken;
+pub use audio::AudioTarget;
+pub use auth_token::{AuthToken, TokenManager};
 pub use error::CatapultError;
 pub use bridge::Bridge;
 pub use call::Call;
 pub use call_event::CallEvent;
 pub use client::Client;
+pub use compliance::Compliance;
 pub use conference::Conference;
+pub use conference_event::ConferenceEvent;
+pub use conversation::Conversation;
+pub use directory_sync::DirectorySync;
 pub use domain::Domain;
 pub use endpoint::Endpoint;
 pub use environment::Environment;
 pub use media::Media;
 pub use message::Message;
 pub use number::Number;
+pub use number_info::NumberInfo;
+pub use phone_number::PhoneNumber;
+pub use scheduler::Scheduler;
 pub use voice::Voice;
 
 
 pub mod prelude{
-	pub use {Account, CatapultError, CatapultResult, Client, Environment, Voice};
+	pub use {Account, AudioTarget, CatapultError, CatapultResult, Client, Environment, PhoneNumber, Voice};
 	pub use {application, call, call_event, message, number};
 }
 
@@ -1,11 +1,130 @@
 use {CatapultResult, CatapultError};
-use client::{EmptyResponse, ByteResponse};
+use client::{EmptyResponse, ByteResponse, JsonResponse, TypedBody};
 use client::Client;
 use hyper::header;
 use lazy::Lazy;
 use lazy::Lazy::*;
 use std::sync::{Arc, Mutex};
 use hyper::header::Headers;
+use hyper::mime::Mime;
+use std::io::{self, Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use util;
+use self::info::MediaInfo;
+use std::path::Path;
+
+///Guesses a mime type from a file's extension, falling back to its first bytes.
+///Only signatures that belong to a single format are used: mp4 containers are matched by
+///brand, so m4a audio and HEIC/AVIF photos aren't taken for video, and an ID3 tag is skipped
+///since it can start several audio formats. Returns "application/octet-stream" if neither is recognized
+pub fn guess_content_type(filename: &str, data: &[u8]) -> String{
+	let extension = Path::new(filename).extension()
+		.and_then(|ext| ext.to_str())
+		.map(|ext| ext.to_lowercase())
+		.unwrap_or(String::new());
+	let by_extension = match extension.as_ref(){
+		"png" => Some("image/png"),
+		"jpg" | "jpeg" => Some("image/jpeg"),
+		"gif" => Some("image/gif"),
+		"bmp" => Some("image/bmp"),
+		"wav" => Some("audio/wav"),
+		"mp3" => Some("audio/mpeg"),
+		"ogg" => Some("audio/ogg"),
+		"amr" => Some("audio/amr"),
+		"m4a" => Some("audio/mp4"),
+		"3gp" => Some("video/3gpp"),
+		"mp4" => Some("video/mp4"),
+		"webm" => Some("video/webm"),
+		"pdf" => Some("application/pdf"),
+		"vcf" => Some("text/vcard"),
+		"txt" => Some("text/plain"),
+		_ => None
+	};
+	if let Some(content_type) = by_extension{
+		return content_type.to_owned()
+	}
+	let magic: &[(&[u8], usize, &str)] = &[
+		(b"\x89PNG\r\n\x1A\n", 0, "image/png"),
+		(b"\xFF\xD8\xFF", 0, "image/jpeg"),
+		(b"GIF87a", 0, "image/gif"),
+		(b"GIF89a", 0, "image/gif"),
+		(b"WAVE", 8, "audio/wav"),
+		(b"OggS", 0, "audio/ogg"),
+		(b"#!AMR", 0, "audio/amr"),
+		(b"ftypM4A ", 4, "audio/mp4"),
+		(b"ftyp3gp", 4, "video/3gpp"),
+		(b"ftypisom", 4, "video/mp4"),
+		(b"ftypiso2", 4, "video/mp4"),
+		(b"ftypmp41", 4, "video/mp4"),
+		(b"ftypmp42", 4, "video/mp4"),
+		(b"ftypavc1", 4, "video/mp4"),
+		(b"%PDF-", 0, "application/pdf"),
+		(b"BEGIN:VCARD", 0, "text/vcard")
+	];
+	for &(bytes, offset, content_type) in magic{
+		if data.len() >= offset + bytes.len() && &data[offset..offset + bytes.len()] == bytes{
+			return content_type.to_owned()
+		}
+	}
+	"application/octet-stream".to_owned()
+}
+fn parse_content_type(content_type: &str) -> CatapultResult<header::ContentType>{
+	let mime: Mime = try!(content_type.parse().map_err(|_|{
+		CatapultError::bad_input(&format!("invalid content type: {}", content_type))
+	}));
+	Ok(header::ContentType(mime))
+}
+
+mod info{
+	#![allow(non_snake_case)]
+	#[derive(RustcDecodable)]
+	pub struct MediaInfo{
+		pub contentLength: u64,
+		pub mediaName: String,
+		pub content: String
+	}
+}
+
+pub struct QueryResult{
+	client: Client,
+	data: Vec<Media>,
+	next_url: Option<String>
+}
+impl QueryResult{
+	pub fn get_media(&self) -> &Vec<Media>{
+		&self.data
+	}
+	pub fn has_next(&self) -> bool{
+		self.next_url.is_some()
+	}
+	pub fn next(&self) -> Option<CatapultResult<QueryResult>>{
+		self.next_url.as_ref().map(|ref url|{
+			Media::list(&self.client, &url, ())
+		})
+	}
+}
+
+pub struct Query{
+	client: Client,
+	size: Option<u32>
+}
+impl Query{
+	pub fn size(mut self, size: u32) -> Query{
+		self.size = Some(size); self
+	}
+	pub fn submit(&self) -> CatapultResult<QueryResult>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/media";
+		
+		let mut map = BTreeMap::new();
+		if let Some(size) = self.size{
+			map.insert("size".to_owned(), size.to_json());
+		}
+		let json = Json::Object(map);
+		Media::list(&self.client, &path, json)
+	}
+}
 
 pub trait ToBytes{
 	fn to_bytes(self) -> Vec<u8>;
@@ -33,12 +152,37 @@ pub struct Media{
 	data: Arc<Mutex<Data>>
 }
 impl Media{
+	///The content type is guessed from the filename and data, see guess_content_type()
 	pub fn create<T>(client: &Client, filename: &str, data: T) -> CatapultResult<Media>
 	where T: ToBytes{
-		
 		let bytes = data.to_bytes();
+		let content_type = guess_content_type(filename, &bytes);
+		Self::create_with_content_type(client, filename, bytes, &content_type)
+	}
+	///`content_type` is a mime type, eg: "image/jpeg"
+	pub fn create_with_content_type<T>(client: &Client, filename: &str, data: T, content_type: &str) -> CatapultResult<Media>
+	where T: ToBytes{
+		let body = TypedBody{
+			content_type: try!(parse_content_type(content_type)),
+			data: data.to_bytes()
+		};
+		let path = "users/".to_string() + &client.get_user_id() + "/media/" + &filename;
+		let res:EmptyResponse = try!(client.raw_put_request(&path, (), body));
+		let data = try!(Self::load_metadata_from_headers(&res.headers));
+		
+		Ok(Media{
+			client: client.clone(),
+			filename: filename.to_owned(),
+			data: Arc::new(Mutex::new(data))
+		})
+	}
+	///Uploads `content_length` bytes from `reader` without holding them all in memory.
+	///`content_type` is a mime type, eg: "audio/wav"
+	pub fn create_from_reader<R>(client: &Client, filename: &str, mut reader: R, content_length: u64, content_type: &str) -> CatapultResult<Media>
+	where R: Read{
+		let content_type = try!(parse_content_type(content_type));
 		let path = "users/".to_string() + &client.get_user_id() + "/media/" + &filename;
-		let res:EmptyResponse = try!(client.raw_put_request(&path, (), bytes));
+		let res:EmptyResponse = try!(client.raw_put_stream_request(&path, (), &mut reader, content_length, content_type));
 		let data = try!(Self::load_metadata_from_headers(&res.headers));
 		
 		Ok(Media{
@@ -54,14 +198,78 @@ impl Media{
 			data: Arc::new(Mutex::new(Data{
 				content_type: NotLoaded,
 				date: NotLoaded,
+				last_modified: NotLoaded,
 				content_length: NotLoaded
 			}))
 		}
 	}
+	pub fn query(client: &Client) -> Query{
+		Query{
+			client: client.clone(),
+			size: None
+		}
+	}
+	fn list<P: json::ToJson>(client: &Client, path: &str, params: P) -> CatapultResult<QueryResult>{
+		let res:JsonResponse<Vec<MediaInfo>> = try!(client.raw_get_request(&path, params, ()));
+		let mut output = vec!();
+		for info in res.body{
+			output.push(Media{
+				client: client.clone(),
+				filename: info.mediaName,
+				data: Arc::new(Mutex::new(Data{
+					content_type: NotLoaded,
+					date: NotLoaded,
+					last_modified: NotLoaded,
+					content_length: Available(info.contentLength)
+				}))
+			});
+		}
+		let next_url = try!(util::get_next_link_from_headers(&res.headers));
+		Ok(QueryResult{
+			client: client.clone(),
+			data: output,
+			next_url: next_url
+		})
+	}
+	///Deletes all media last modified more than `age` ago. Each media is loaded to find its age,
+	///and media without a Last-Modified time is left alone.
+	///Returns each media that was old enough to delete, with the result of deleting it.
+	///Media whose age couldn't be loaded is returned with that error, and the rest are still deleted
+	pub fn delete_older_than(client: &Client, age: Duration) -> CatapultResult<Vec<(Media, CatapultResult<()>)>>{
+		let cutoff = SystemTime::now() - age;
+		let mut old_media = vec!();
+		let mut results = vec!();
+		let mut page = try!(Self::query(client).submit());
+		loop{
+			for media in page.get_media(){
+				match media.get_last_modified(){
+					Ok(Some(last_modified)) => if last_modified < cutoff{
+						old_media.push(media.clone());
+					},
+					Ok(None) => {},
+					Err(err) => results.push((media.clone(), Err(err)))
+				}
+			}
+			page = match page.next(){
+				Some(next) => try!(next),
+				None => break
+			};
+		}
+		//delete after listing, so pages aren't shifted while they are read
+		results.extend(old_media.into_iter().map(|media|{
+			let result = media.delete();
+			(media, result)
+		}));
+		Ok(results)
+	}
 	fn load_metadata_from_headers(headers: &Headers) -> CatapultResult<Data>{
 		let content_type;
 		let content_length;
 		let date;
+		let last_modified = headers.get::<header::LastModified>().map(|header|{
+			let secs = (header.0).0.to_timespec().sec;
+			UNIX_EPOCH + Duration::from_secs(if secs > 0 {secs as u64} else {0})
+		});
 		match headers.get::<header::ContentType>(){
 			Some(content_type_header) => {
 				let mime = &content_type_header.0;
@@ -85,9 +293,15 @@ impl Media{
 		Ok(Data{
 			content_type: Available(content_type),
 			date: Available(date),
+			last_modified: Available(last_modified),
 			content_length: Available(content_length)
 		})
 	}
+	pub fn delete(&self) -> CatapultResult<()>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/media/" + &self.filename;
+		let _:EmptyResponse = try!(self.client.raw_delete_request(&path, ()));
+		Ok(())
+	}
 	pub fn load(&self) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/media/" + &self.filename;
 		let res:EmptyResponse = try!(self.client.raw_head_request(&path, (), ()));
@@ -103,6 +317,11 @@ impl Media{
 	pub fn get_filename(&self) -> String{
 		self.filename.clone()
 	}
+	///The absolute url of this media, as used when sending a message
+	pub fn get_url(&self) -> CatapultResult<String>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/media/" + &self.filename;
+		Ok(try!(self.client.make_absolute_url(&path)).to_string())
+	}
 	
 	pub fn get_content_type(&self) -> CatapultResult<String>{
 		lazy_load!(self, content_type)
@@ -113,12 +332,27 @@ impl Media{
 	pub fn get_content_length(&self) -> CatapultResult<u64>{
 		lazy_load!(self, content_length)
 	}
+	pub fn get_last_modified(&self) -> CatapultResult<Option<SystemTime>>{
+		lazy_load!(self, last_modified)
+	}
 	pub fn get_contents(&self) -> CatapultResult<Vec<u8>>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/media/" + &self.filename;
 		let res:ByteResponse = try!(self.client.raw_get_request(&path, (), ()));
 		try!(Self::load_metadata_from_headers(&res.headers));
 		Ok(res.body)
 	}
+	///Streams the contents into `writer` without holding them all in memory.
+	///Returns the number of bytes written
+	pub fn download_to<W>(&self, writer: &mut W) -> CatapultResult<u64>
+	where W: Write{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/media/" + &self.filename;
+		let mut res = try!(self.client.raw_get_stream_request(&path, ()));
+		//a streamed response may not have a Content-Length, so the metadata is optional here
+		if let Ok(data) = Self::load_metadata_from_headers(&res.headers){
+			*self.data.lock().unwrap() = data;
+		}
+		Ok(try!(io::copy(&mut res, writer)))
+	}
 	pub fn get_contents_as_string(&self) -> CatapultResult<String>{
 		let body = try!(self.get_contents());
 		Ok(try!(String::from_utf8(body)))
@@ -128,5 +362,47 @@ impl Media{
 struct Data{
 	content_type: Lazy<String>,
 	date: Lazy<String>,
+	last_modified: Lazy<Option<SystemTime>>,
 	content_length: Lazy<u64>
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests{
+	use super::guess_content_type;
+
+	fn mp4_header(brand: &[u8]) -> Vec<u8>{
+		let mut data = b"\x00\x00\x00\x18ftyp".to_vec();
+		data.extend_from_slice(brand);
+		data
+	}
+
+	#[test]
+	fn extension_wins_over_magic_bytes(){
+		assert_eq!(guess_content_type("song.m4a", &mp4_header(b"isom")), "audio/mp4");
+		assert_eq!(guess_content_type("photo.PNG", b"\xFF\xD8\xFF\xE0"), "image/png");
+		assert_eq!(guess_content_type("notes.txt", b"%PDF-1.4"), "text/plain");
+	}
+	#[test]
+	fn mp4_brands(){
+		assert_eq!(guess_content_type("upload", &mp4_header(b"M4A ")), "audio/mp4");
+		assert_eq!(guess_content_type("upload", &mp4_header(b"isom")), "video/mp4");
+		assert_eq!(guess_content_type("upload", &mp4_header(b"mp42")), "video/mp4");
+		assert_eq!(guess_content_type("upload", &mp4_header(b"3gp4")), "video/3gpp");
+		assert_eq!(guess_content_type("upload", &mp4_header(b"heic")), "application/octet-stream");
+		assert_eq!(guess_content_type("upload", &mp4_header(b"avif")), "application/octet-stream");
+	}
+	#[test]
+	fn magic_bytes_without_extension(){
+		assert_eq!(guess_content_type("upload", b"\x89PNG\r\n\x1A\n\x00"), "image/png");
+		assert_eq!(guess_content_type("upload", b"\xFF\xD8\xFF\xE0"), "image/jpeg");
+		assert_eq!(guess_content_type("upload", b"RIFF\x00\x00\x00\x00WAVEfmt "), "audio/wav");
+		assert_eq!(guess_content_type("upload", b"BEGIN:VCARD\r\n"), "text/vcard");
+	}
+	#[test]
+	fn weak_signatures_are_ignored(){
+		assert_eq!(guess_content_type("upload", b"BM\x00\x00"), "application/octet-stream");
+		assert_eq!(guess_content_type("upload", b"ID3\x04\x00"), "application/octet-stream");
+		assert_eq!(guess_content_type("upload", b"\xFF\xFB\x90"), "application/octet-stream");
+		assert_eq!(guess_content_type("upload", b""), "application/octet-stream");
+	}
+}
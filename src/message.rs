@@ -1,15 +1,25 @@
 use {CatapultResult, CatapultError};
-use client::{EmptyResponse, JsonResponse, Client};
+use client::{EmptyResponse, JsonResponse, RawJsonResponse, Client};
 use std::sync::{Arc, Mutex};
 use lazy::Lazy;
 use lazy::Lazy::*;
 use util;
 use message_event::MessageEvent;
-use media::Media;
+use media::{self, Media};
 use self::info::MessageInfo;
 use std::collections::BTreeMap;
 use rustc_serialize::json::{ToJson, Json};
 use rustc_serialize::json;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use scheduler::Scheduler;
+use compliance;
+use phone_number::PhoneNumber;
+
+///Catapult rejects messages whose media adds up to more than this many bytes
+pub const MAX_MEDIA_SIZE: usize = 2000000;
 
 
 pub struct QueryResult{
@@ -34,14 +44,21 @@ impl QueryResult{
 mod info{
 	#![allow(non_snake_case)]
 	use error::ApiError;
+	use rustc_serialize::json::Json;
+	use {util, CatapultResult};
 	
+	///"to" is a list of numbers for group messages, otherwise a single number
+	pub fn decode_message(mut json: Json) -> CatapultResult<MessageInfo>{
+		util::string_to_array(&mut json, "to");
+		util::decode_json(json)
+	}
 	
 	#[derive(RustcDecodable)]
 	pub struct MessageInfo{
 		pub id: String,
 		pub direction: String,
 		pub from: String,
-		pub to: String,
+		pub to: Vec<String>,
 		pub state: String,
 		pub text: String,
 		pub time: String,
@@ -88,10 +105,31 @@ impl State{
 		}
 	}
 }
+///Best effort, the media is only wasting storage if this fails
+fn delete_media(media: Vec<Media>){
+	for media in media{
+		let _ = media.delete();
+	}
+}
+fn find_opted_out(client: &Client, numbers: &Vec<String>) -> CatapultResult<Option<String>>{
+	if let Some(store) = client.get_opt_out_store(){
+		for number in numbers{
+			if try!(store.is_opted_out(&compliance::normalize_number(number))){
+				return Ok(Some(number.clone()))
+			}
+		}
+	}
+	Ok(None)
+}
 fn get_message_list<P: json::ToJson>(client: &Client, path: &str, params: P) -> CatapultResult<QueryResult>{
-	let res:JsonResponse<Vec<MessageInfo>> = try!(client.raw_get_request(&path, params, ()));
+	let res:RawJsonResponse = try!(client.raw_get_request(&path, params, ()));
+	let list = match res.body{
+		Json::Array(list) => list,
+		_ => return Err(CatapultError::unexpected("expected a list of messages"))
+	};
 	let mut output = vec!();
-	for info in res.body{
+	for json in list{
+		let info = try!(info::decode_message(json));
 		output.push(Message{
 			id: info.id.clone(),
 			client: client.clone(),
@@ -123,11 +161,11 @@ impl Query{
 	pub fn to_time(mut self, time: &str) -> Query{
 		self.to_time = Some(time.to_owned()); self
 	}
-	pub fn from_number(mut self, number: &str) -> Query{
-		self.from_number = Some(number.to_owned()); self
+	pub fn from_number<N: Into<PhoneNumber>>(mut self, number: N) -> Query{
+		self.from_number = Some(number.into().get_e164()); self
 	}
-	pub fn to_number(mut self, number: &str) -> Query{
-		self.to_number = Some(number.to_owned()); self
+	pub fn to_number<N: Into<PhoneNumber>>(mut self, number: N) -> Query{
+		self.to_number = Some(number.into().get_e164()); self
 	}
 	pub fn size(mut self, size: u32) -> Query{
 		self.size = Some(size); self
@@ -177,10 +215,34 @@ impl Query{
 		get_message_list(&self.client, &path, json)
 	}
 }
+///A PendingMessage without its client, so it can be stored and sent later
+#[derive(RustcEncodable, RustcDecodable, Clone, Debug)]
+pub struct SavedMessage{
+	from: String,
+	to: Vec<String>,
+	text: String,
+	media: Vec<String>,
+	receipt_requested: String,
+	callback_url: Option<String>,
+	callback_http_method: String,
+	callback_timeout: Option<u64>,
+	fallback_url: Option<String>,
+	tag: Option<String>
+}
+
+enum Attachment{
+	File(PathBuf),
+	Bytes{
+		name: String,
+		data: Vec<u8>,
+		content_type: String
+	}
+}
+
 pub struct PendingMessage{
 	client: Client,
 	from: String,
-	to: String,
+	to: Vec<String>,
 	text: String,
 	media: Vec<String>,
 	receipt_requested: String,
@@ -188,12 +250,62 @@ pub struct PendingMessage{
 	callback_http_method: String,
 	callback_timeout: Option<u64>,
 	fallback_url: Option<String>,
-	tag: Option<String>
+	tag: Option<String>,
+	attachments: Vec<Attachment>,
+	delete_uploads: bool,
+	check_opt_out: bool
 }
 impl PendingMessage{
+	fn new(client: &Client, from: &str, to: Vec<String>, text: &str) -> PendingMessage{
+		PendingMessage{
+			client: client.clone(),
+			from: from.to_owned(),
+			to: to,
+			text: text.to_owned(),
+			media: vec!(),
+			receipt_requested: "none".to_owned(),
+			callback_url: None,
+			callback_http_method: "POST".to_owned(),
+			callback_timeout: None,
+			fallback_url: None,
+			tag: None,
+			attachments: vec!(),
+			delete_uploads: false,
+			check_opt_out: true
+		}
+	}
+	///Adds another recipient, making this a group message
+	pub fn recipient<N: Into<PhoneNumber>>(mut self, number: N) -> Self{
+		self.to.push(number.into().get_e164()); self
+	}
 	pub fn media(mut self, url: &str) -> Self{
 		self.media.push(url.to_owned()); self
 	}
+	///The file is read and uploaded as media when the message is sent.
+	///Its content type is guessed from its contents and extension
+	pub fn attach_file<P: AsRef<Path>>(mut self, path: P) -> Self{
+		self.attachments.push(Attachment::File(path.as_ref().to_owned())); self
+	}
+	///Uploaded as media when the message is sent. `name` is kept at the end of the media name
+	pub fn attach_bytes(mut self, name: &str, data: Vec<u8>, content_type: &str) -> Self{
+		self.attachments.push(Attachment::Bytes{
+			name: name.to_owned(),
+			data: data,
+			content_type: content_type.to_owned()
+		}); self
+	}
+	///Deletes the uploaded attachments as soon as the message is accepted.
+	///Catapult may fetch the media after that, when it delivers the message, so recipients
+	///can get the message without its media. Without this, uploads are kept and can be
+	///cleaned up later with Client::delete_media_older_than()
+	pub fn delete_uploads_after_send(mut self) -> Self{
+		self.delete_uploads = true; self
+	}
+	///Sends even to numbers that opted out. Only for replies carriers require,
+	///such as the confirmation of a STOP or the answer to a HELP
+	pub fn skip_opt_out_check(mut self) -> Self{
+		self.check_opt_out = false; self
+	}
 	pub fn request_receipt(mut self) -> Self{
 		self.receipt_requested = "all".to_owned(); self
 	}
@@ -214,9 +326,14 @@ impl PendingMessage{
 		self.tag = Some(tag.to_owned()); self
 	}
 	fn to_json(&self) -> Json{
+		let to = if self.to.len() == 1{
+			self.to[0].to_json()
+		}else{
+			self.to.to_json()
+		};
 		json!({
 			"from" => (self.from),
-			"to" => (self.to),
+			"to" => (to),
 			"text" => (self.text),
 			"media" => (self.media),
 			"receiptRequested" => (self.receipt_requested),
@@ -227,7 +344,125 @@ impl PendingMessage{
 			"tag" => (self.tag)
 		})
 	}
-	pub fn create(self) -> CatapultResult<Message>{
+	///Attachments are only uploaded when a message is sent, so they can't be saved.
+	///Upload them with Client::create_media() and add the url with media() instead
+	pub fn to_saved(&self) -> CatapultResult<SavedMessage>{
+		if !self.attachments.is_empty(){
+			return Err(CatapultError::bad_input("a message with attachments can't be saved"))
+		}
+		Ok(SavedMessage{
+			from: self.from.clone(),
+			to: self.to.clone(),
+			text: self.text.clone(),
+			media: self.media.clone(),
+			receipt_requested: self.receipt_requested.clone(),
+			callback_url: self.callback_url.clone(),
+			callback_http_method: self.callback_http_method.clone(),
+			callback_timeout: self.callback_timeout,
+			fallback_url: self.fallback_url.clone(),
+			tag: self.tag.clone()
+		})
+	}
+	pub fn from_saved(client: &Client, saved: &SavedMessage) -> PendingMessage{
+		PendingMessage{
+			client: client.clone(),
+			from: saved.from.clone(),
+			to: saved.to.clone(),
+			text: saved.text.clone(),
+			media: saved.media.clone(),
+			receipt_requested: saved.receipt_requested.clone(),
+			callback_url: saved.callback_url.clone(),
+			callback_http_method: saved.callback_http_method.clone(),
+			callback_timeout: saved.callback_timeout,
+			fallback_url: saved.fallback_url.clone(),
+			tag: saved.tag.clone(),
+			attachments: vec!(),
+			delete_uploads: false,
+			check_opt_out: true
+		}
+	}
+	///Sends the message at `send_at` instead of now. Returns the id of the scheduled message
+	pub fn schedule(self, scheduler: &Scheduler, send_at: SystemTime) -> CatapultResult<String>{
+		scheduler.schedule(self, send_at)
+	}
+	///The reason this message can't be sent, if any
+	fn check_recipients(&self) -> CatapultResult<Option<CatapultError>>{
+		if self.to.is_empty(){
+			return Ok(Some(CatapultError::bad_input("message has no recipients")))
+		}
+		if !self.check_opt_out{
+			return Ok(None)
+		}
+		Ok(try!(find_opted_out(&self.client, &self.to)).map(|number|{
+			CatapultError::opted_out(&number)
+		}))
+	}
+	///Uploads attachments and adds their urls to the message media.
+	///Each upload gets a unique name, since media is shared by the whole account.
+	///Uploads are kept after sending unless delete_uploads_after_send() was used.
+	///Returns the uploaded media
+	fn upload_attachments(&mut self) -> CatapultResult<Vec<Media>>{
+		let mut files = vec!();
+		let mut total_size = 0;
+		let too_large = |total_size|{
+			CatapultError::bad_input(&format!(
+				"attachments are at least {} bytes, the limit is {}", total_size, MAX_MEDIA_SIZE
+			))
+		};
+		for attachment in self.attachments.drain(..){
+			let (name, data, content_type) = match attachment{
+				Attachment::File(path) => {
+					let name = try!(path.file_name().and_then(|name| name.to_str()).ok_or(
+						CatapultError::bad_input(&format!("invalid attachment filename: {}", path.display()))
+					)).to_owned();
+					//checked before reading, so a huge file isn't loaded just to be refused
+					let file_size = try!(fs::metadata(&path)).len() as usize;
+					if total_size + file_size > MAX_MEDIA_SIZE{
+						return Err(too_large(total_size + file_size))
+					}
+					let mut data = vec!();
+					try!(try!(File::open(&path)).read_to_end(&mut data));
+					let content_type = media::guess_content_type(&name, &data);
+					(name, data, content_type)
+				},
+				Attachment::Bytes{name, data, content_type} => (name, data, content_type)
+			};
+			total_size += data.len();
+			files.push((name, data, content_type));
+		}
+		if total_size > MAX_MEDIA_SIZE{
+			return Err(too_large(total_size))
+		}
+		let mut uploads = vec!();
+		for (name, data, content_type) in files{
+			let name = util::generate_id() + "-" + &name;
+			let result = Media::create_with_content_type(&self.client, &name, data, &content_type).and_then(|media|{
+				self.media.push(try!(media.get_url()));
+				Ok(media)
+			});
+			match result{
+				Ok(media) => uploads.push(media),
+				Err(err) => {
+					delete_media(uploads);
+					return Err(err)
+				}
+			}
+		}
+		Ok(uploads)
+	}
+	pub fn create(mut self) -> CatapultResult<Message>{
+		if let Some(err) = try!(self.check_recipients()){
+			return Err(err)
+		}
+		let uploads = try!(self.upload_attachments());
+		let delete_uploads = self.delete_uploads;
+		let result = self.send();
+		if delete_uploads || result.is_err(){
+			delete_media(uploads);
+		}
+		result
+	}
+	fn send(self) -> CatapultResult<Message>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/messages";
 		let res:EmptyResponse = try!(self.client.raw_post_request(&path, (), &self.to_json()));
 		let id = try!(util::get_id_from_location_header(&res.headers));
@@ -237,7 +472,8 @@ impl PendingMessage{
 			data: Arc::new(Mutex::new(Data{
 				inbound: Available(false),
 				from: Available(self.from.clone()),
-				to: Available(self.to.clone()),
+				to: Available(self.to[0].clone()),
+				recipients: Available(self.to.clone()),
 				state: NotLoaded,
 				text: Available(self.text.clone()),
 				time: NotLoaded,
@@ -245,28 +481,83 @@ impl PendingMessage{
 			}))
 		})
 	}
+	///Returns one result per message, in the same order. Messages refused before sending,
+	///such as those to opted-out numbers, get an Err without stopping the others
 	pub fn batch_send<I>(client: &Client, messages: I) -> CatapultResult<Vec<CatapultResult<Message>>>
 	where I: IntoIterator<Item = PendingMessage>{
-		let messages:Vec<PendingMessage> = messages.into_iter().collect();
+		let mut messages:Vec<PendingMessage> = messages.into_iter().collect();
 		let mut msg_list = vec!();
-		for msg in messages.iter(){
-			msg_list.push(msg.to_json());
+		let mut refused = vec!();
+		let mut uploads = vec!();
+		for msg in messages.iter_mut(){
+			let mut err = match msg.check_recipients(){
+				Ok(refusal) => refusal,
+				Err(check_err) => Some(check_err)
+			};
+			let mut media = vec!();
+			if err.is_none(){
+				match msg.upload_attachments(){
+					Ok(uploaded) => {
+						media = uploaded;
+						msg_list.push(msg.to_json());
+					},
+					Err(upload_err) => err = Some(upload_err)
+				}
+			}
+			refused.push(err);
+			uploads.push(media);
+		}
+		let mut infos = vec!();
+		if !msg_list.is_empty(){
+			let json = Json::Array(msg_list);
+			let path = "users/".to_string() + &client.get_user_id() + "/messages";
+			//same as create(), uploads are deleted when their message wasn't sent or delete_uploads is set
+			let res:JsonResponse<Vec<info::BatchMessageInfo>> = match client.raw_post_request(&path, (), &json){
+				Ok(res) => res,
+				Err(err) => {
+					for media in uploads{
+						delete_media(media);
+					}
+					return Err(err)
+				}
+			};
+			infos = res.body;
 		}
-		let json = Json::Array(msg_list);
-		let path = "users/".to_string() + &client.get_user_id() + "/messages";
-		let res:JsonResponse<Vec<info::BatchMessageInfo>> = try!(client.raw_post_request(&path, (), &json));
 		
+		let mut infos = infos.into_iter();
 		let mut output = vec!();
-		for (msg, info) in messages.iter().zip(res.body){
+		for ((msg, err), media) in messages.iter().zip(refused).zip(uploads){
+			if let Some(err) = err{
+				output.push(Err(err));
+				continue;
+			}
+			//every message gets a result, so the output lines up with the input
+			let info = match infos.next(){
+				Some(info) => info,
+				None => {
+					output.push(Err(CatapultError::unexpected("no result for message in batch response")));
+					continue;
+				}
+			};
 			if let Some(location) = info.location{
-				let id = try!(util::get_id_from_location_url(&location));
+				if msg.delete_uploads{
+					delete_media(media);
+				}
+				let id = match util::get_id_from_location_url(&location){
+					Ok(id) => id,
+					Err(err) => {
+						output.push(Err(err));
+						continue;
+					}
+				};
 				output.push(Ok(Message{
 					id: id,
 					client: client.clone(),
 					data: Arc::new(Mutex::new(Data{
 						inbound: Available(false),
 						from: Available(msg.from.clone()),
-						to: Available(msg.to.clone()),
+						to: Available(msg.to[0].clone()),
+						recipients: Available(msg.to.clone()),
 						state: NotLoaded,
 						text: Available(msg.text.clone()),
 						time: NotLoaded,
@@ -274,7 +565,10 @@ impl PendingMessage{
 					}))
 				}))
 			}else if let Some(err) = info.error{
+				delete_media(media);
 				output.push(Err(CatapultError::ApiError(err)));
+			}else{
+				output.push(Err(CatapultError::unexpected("batch result has neither a location nor an error")));
 			}
 		}
 		
@@ -286,6 +580,7 @@ struct Data{
 	inbound: Lazy<bool>,
 	from: Lazy<String>,
 	to: Lazy<String>,
+	recipients: Lazy<Vec<String>>,
 	state: Lazy<State>,
 	text: Lazy<String>,
 	time: Lazy<String>,
@@ -302,7 +597,10 @@ impl Data{
 				))
 			}),
 			from: Available(info.from.clone()),
-			to: Available(info.to.clone()),
+			to: Available(try!(info.to.first().cloned().ok_or(
+				CatapultError::unexpected("Message has no recipients")
+			))),
+			recipients: Available(info.to.clone()),
 			state: Available(try!(State::parse(&info.state))),
 			text: Available(info.text.clone()),
 			time: Available(info.time.clone()),
@@ -323,26 +621,27 @@ impl Data{
 	}
 }
 
+#[derive(Clone)]
 pub struct Message{
 	id: String,
 	client: Client,
 	data: Arc<Mutex<Data>>
 }
 impl Message{
-	pub fn build(client: &Client, from: &str, to: &str, text: &str) -> PendingMessage{
-		PendingMessage{
-			client: client.clone(),
-			from: from.to_owned(),
-			to: to.to_owned(),
-			text: text.to_owned(),
-			media: vec!(),
-			receipt_requested: "none".to_owned(),
-			callback_url: None,
-			callback_http_method: "POST".to_owned(),
-			callback_timeout: None,
-			fallback_url: None,
-			tag: None
-		}
+	pub fn build<F, T>(client: &Client, from: F, to: T, text: &str) -> PendingMessage
+	where F: Into<PhoneNumber>, T: Into<PhoneNumber>{
+		PendingMessage::new(client, &from.into().get_e164(), vec!(to.into().get_e164()), text)
+	}
+	///A group message to every number in `to`
+	pub fn build_group<F, I>(client: &Client, from: F, to: I, text: &str) -> PendingMessage
+	where F: Into<PhoneNumber>, I: IntoIterator, I::Item: Into<PhoneNumber>{
+		let to = to.into_iter().map(|number| number.into().get_e164()).collect();
+		PendingMessage::new(client, &from.into().get_e164(), to, text)
+	}
+	///A reply from the number the event was sent to. The numbers come from Catapult,
+	///so short codes and other numbers PhoneNumber can't parse work too
+	pub fn build_reply(event: &MessageEvent, text: &str) -> PendingMessage{
+		PendingMessage::new(&event.get_client(), &event.get_to(), vec!(event.get_from()), text)
 	}
 	pub fn query(client: &Client) -> Query{
 		Query{
@@ -365,6 +664,7 @@ impl Message{
 				inbound: NotLoaded,
 				from: NotLoaded,
 				to: NotLoaded,
+				recipients: NotLoaded,
 				state: NotLoaded,
 				text: NotLoaded,
 				time: NotLoaded,
@@ -380,6 +680,7 @@ impl Message{
 				inbound: Available(event.is_inbound()),
 				from: Available(event.get_from()),
 				to: Available(event.get_to()),
+				recipients: Available(event.get_recipients()),
 				state: NotLoaded,
 				text: Available(event.get_text()),
 				time: Available(event.get_time()),
@@ -389,9 +690,10 @@ impl Message{
 	}
 	pub fn load(&self) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/messages/" + &self.id;
-		let res:JsonResponse<MessageInfo> = try!(self.client.raw_get_request(&path, (), ()));
+		let res:RawJsonResponse = try!(self.client.raw_get_request(&path, (), ()));
+		let info = try!(info::decode_message(res.body));
 		let mut data = self.data.lock().unwrap();
-		*data = try!(Data::from_info(&self.client, &res.body));
+		*data = try!(Data::from_info(&self.client, &info));
 		Ok(())
 	}
 	
@@ -414,9 +716,22 @@ impl Message{
 	pub fn get_from(&self) -> CatapultResult<String>{
 		lazy_load!(self, from)
 	}
+	///The first recipient. Use get_recipients() for group messages
 	pub fn get_to(&self) -> CatapultResult<String>{
 		lazy_load!(self, to)
 	}
+	pub fn get_recipients(&self) -> CatapultResult<Vec<String>>{
+		lazy_load!(self, recipients)
+	}
+	///The sender followed by every recipient
+	pub fn get_participants(&self) -> CatapultResult<Vec<String>>{
+		let mut participants = vec!(try!(self.get_from()));
+		participants.extend(try!(self.get_recipients()));
+		Ok(participants)
+	}
+	pub fn is_group(&self) -> CatapultResult<bool>{
+		Ok(try!(self.get_recipients()).len() > 1)
+	}
 	pub fn get_state(&self) -> CatapultResult<State>{
 		lazy_load!(self, state)
 	}
@@ -1,8 +1,7 @@
 use {CatapultResult, CatapultError};
 use client::Client;
 use self::info::MessageEventInfo;
-use rustc_serialize::json;
-use message::{Message, State};
+use message::{Message, PendingMessage, State};
 use application::Application;
 use media::Media;
 use util;
@@ -11,6 +10,7 @@ pub struct MessageEvent{
 	client: Client,
 	message_id: String,
 	to: String,
+	recipients: Vec<String>,
 	from: String,
 	time: String,
 	text: String,
@@ -21,11 +21,17 @@ pub struct MessageEvent{
 }
 impl MessageEvent{
 	pub fn parse(client: &Client, data: &str) -> CatapultResult<MessageEvent>{
-		let info: MessageEventInfo = try!(json::decode(data));
+		//"to" is a list of numbers for group messages, otherwise a single number
+		let mut json = try!(util::parse_json(data));
+		util::string_to_array(&mut json, "to");
+		let info: MessageEventInfo = try!(util::decode_json(json));
 		Ok(MessageEvent{
 			client: client.clone(),
 			message_id: info.messageId.clone(),
-			to: info.to.clone(),
+			to: try!(info.to.first().cloned().ok_or(
+				CatapultError::unexpected("MessageEvent has no recipients")
+			)),
+			recipients: info.to.clone(),
 			from: info.from.clone(),
 			time: info.time.clone(),
 			text: info.text.clone(),
@@ -62,9 +68,25 @@ impl MessageEvent{
 	pub fn get_message(&self) -> Message{
 		Message::from_event(self)
 	} 
+	pub fn build_reply(&self, text: &str) -> PendingMessage{
+		Message::build_reply(self, text)
+	}
+	///The first recipient. Use get_recipients() for group messages
 	pub fn get_to(&self) -> String{
 		self.to.clone()
 	}
+	pub fn get_recipients(&self) -> Vec<String>{
+		self.recipients.clone()
+	}
+	///The sender followed by every recipient
+	pub fn get_participants(&self) -> Vec<String>{
+		let mut participants = vec!(self.from.clone());
+		participants.extend(self.recipients.iter().cloned());
+		participants
+	}
+	pub fn is_group(&self) -> bool{
+		self.recipients.len() > 1
+	}
 	pub fn get_from(&self) -> String{
 		self.from.clone()
 	}
@@ -96,7 +118,7 @@ mod info{
 	#![allow(non_snake_case)]
 	#[derive(RustcDecodable)]
 	pub struct MessageEventInfo{
-		pub to: String,
+		pub to: Vec<String>,
 		pub from: String,
 		pub time: String,
 		pub text: String,
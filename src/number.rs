@@ -1,4 +1,4 @@
-use {Client, CatapultResult};
+use {Client, CatapultResult, PhoneNumber};
 use client::{JsonResponse, EmptyResponse};
 use util;
 use std::sync::{Mutex, Arc};
@@ -6,7 +6,7 @@ use lazy::Lazy;
 use lazy::Lazy::*;
 use rustc_serialize::json::{Json, ToJson};
 use std::collections::BTreeMap;
-use self::info::{NumberInfo, AllocatedNumber};
+use self::info::{NumberInfo, AllocatedNumber, AvailableNumberInfo};
 
 pub enum Search{
 	ByCity{
@@ -19,17 +19,221 @@ pub enum Search{
 		area_code: String,
 		local_number: String,
 		in_local_calling_area: bool
+	},
+	///`?` matches any single digit and `*` matches any number of digits. eg: 919555****
+	ByPattern(String)
+}
+impl Search{
+	fn to_json(self, quantity: u32) -> Json{
+		match self{
+			Search::ByCity{city, state} => json!({
+				"city" => (city),
+				"state" => (state),
+				"quantity" => (quantity)
+			}),
+			Search::ByState(state) => json!({
+				"state" => (state),
+				"quantity" => (quantity)
+			}),
+			Search::ByZip(zip) => json!({
+				"zip" => (zip),
+				"quantity" => (quantity)
+			}),
+			Search::ByAreaCode{
+				area_code, local_number, in_local_calling_area
+			} => json!({
+				"areaCode" => (area_code),
+				"localNumber" => (local_number),
+				"inLocalCallingArea" => (in_local_calling_area),
+				"quantity" => (quantity)
+			}),
+			Search::ByPattern(pattern) => json!({
+				"pattern" => (pattern),
+				"quantity" => (quantity)
+			})
+		}
+	}
+}
+
+pub enum TollFreeSearch{
+	Any,
+	///eg: "800", "888" or "877"
+	ByPrefix(String),
+	///`?` matches any single digit and `*` matches any number of digits. eg: 888555****
+	ByPattern(String)
+}
+impl TollFreeSearch{
+	fn to_json(self, quantity: u32) -> Json{
+		match self{
+			TollFreeSearch::Any => json!({
+				"quantity" => (quantity)
+			}),
+			TollFreeSearch::ByPrefix(prefix) => json!({
+				"pattern" => (prefix + "*"),
+				"quantity" => (quantity)
+			}),
+			TollFreeSearch::ByPattern(pattern) => json!({
+				"pattern" => (pattern),
+				"quantity" => (quantity)
+			})
+		}
+	}
+}
+
+///A number that can be ordered. Searching for these does not allocate them
+#[derive(Clone, Debug)]
+pub struct AvailableNumber{
+	number: String,
+	national_number: String,
+	city: Option<String>,
+	rate_center: Option<String>,
+	lata: Option<String>,
+	state: Option<String>,
+	price: String
+}
+impl AvailableNumber{
+	fn from_info(info: AvailableNumberInfo) -> AvailableNumber{
+		AvailableNumber{
+			number: info.number,
+			national_number: info.nationalNumber,
+			city: info.city,
+			rate_center: info.rateCenter,
+			lata: info.lata,
+			state: info.state,
+			price: info.price
+		}
+	}
+	fn search(client: &Client, path: &str, params: Json) -> CatapultResult<Vec<AvailableNumber>>{
+		let res:JsonResponse<Vec<AvailableNumberInfo>> = try!(client.raw_get_request(path, params, ()));
+		Ok(res.body.into_iter().map(AvailableNumber::from_info).collect())
+	}
+	pub fn search_local(client: &Client, quantity: u32, search: Search) -> CatapultResult<Vec<AvailableNumber>>{
+		Self::search(client, "availableNumbers/local", search.to_json(quantity))
+	}
+	pub fn search_toll_free(client: &Client, quantity: u32, search: TollFreeSearch) -> CatapultResult<Vec<AvailableNumber>>{
+		Self::search(client, "availableNumbers/tollFree", search.to_json(quantity))
+	}
+	
+	/* Getters */
+	pub fn get_number(&self) -> String{
+		self.number.clone()
+	}
+	pub fn get_national_number(&self) -> String{
+		self.national_number.clone()
+	}
+	///Not available for toll-free numbers
+	pub fn get_city(&self) -> Option<String>{
+		self.city.clone()
+	}
+	///Not available for toll-free numbers
+	pub fn get_rate_center(&self) -> Option<String>{
+		self.rate_center.clone()
+	}
+	///Not available for toll-free numbers
+	pub fn get_lata(&self) -> Option<String>{
+		self.lata.clone()
+	}
+	///Not available for toll-free numbers
+	pub fn get_state(&self) -> Option<String>{
+		self.state.clone()
+	}
+	pub fn get_price(&self) -> String{
+		self.price.clone()
 	}
 }
 
 
 
-pub struct Number<'a>{
+#[derive(Clone)]
+pub struct Number{
 	id: String,
-	client: &'a Client,
+	client: Client,
 	data: Arc<Mutex<Data>>
 }
 
+pub struct QueryResult{
+	client: Client,
+	data: Vec<Number>,
+	next_url: Option<String>,
+	number_pattern: Option<String>
+}
+impl QueryResult{
+	pub fn get_numbers(&self) -> &Vec<Number>{
+		&self.data
+	}
+	pub fn has_next(&self) -> bool{
+		self.next_url.is_some()
+	}
+	pub fn next(&self) -> Option<CatapultResult<QueryResult>>{
+		self.next_url.as_ref().map(|ref url|{
+			Number::list(&self.client, &url, (), self.number_pattern.clone())
+		})
+	}
+}
+
+pub struct Query{
+	client: Client,
+	application_id: Option<String>,
+	state: Option<String>,
+	city: Option<String>,
+	name: Option<String>,
+	number_state: Option<String>,
+	number_pattern: Option<String>,
+	size: Option<u32>
+}
+impl Query{
+	pub fn application_id(mut self, id: &str) -> Self{
+		self.application_id = Some(id.to_owned()); self
+	}
+	///2 letter abbreviation of the state the number is in
+	pub fn state(mut self, state: &str) -> Self{
+		self.state = Some(state.to_owned()); self
+	}
+	pub fn city(mut self, city: &str) -> Self{
+		self.city = Some(city.to_owned()); self
+	}
+	pub fn name(mut self, name: &str) -> Self{
+		self.name = Some(name.to_owned()); self
+	}
+	///"enabled" or "released"
+	pub fn number_state(mut self, state: &str) -> Self{
+		self.number_state = Some(state.to_owned()); self
+	}
+	///`?` matches any single digit and `*` matches any number of digits. eg: 919555****
+	///Catapult can't filter by pattern, so this is applied to each page after it is fetched
+	pub fn number_pattern(mut self, pattern: &str) -> Self{
+		self.number_pattern = Some(pattern.to_owned()); self
+	}
+	pub fn size(mut self, size: u32) -> Self{
+		self.size = Some(size); self
+	}
+	pub fn submit(&self) -> CatapultResult<QueryResult>{
+		let path = "users/".to_string() + &self.client.get_user_id() + "/phoneNumbers";
+		
+		let mut map = BTreeMap::new();
+		if let Some(ref id) = self.application_id{
+			map.insert("applicationId".to_string(), id.to_json());
+		}
+		if let Some(ref state) = self.state{
+			map.insert("state".to_string(), state.to_json());
+		}
+		if let Some(ref city) = self.city{
+			map.insert("city".to_string(), city.to_json());
+		}
+		if let Some(ref name) = self.name{
+			map.insert("name".to_string(), name.to_json());
+		}
+		if let Some(ref number_state) = self.number_state{
+			map.insert("numberState".to_string(), number_state.to_json());
+		}
+		if let Some(size) = self.size{
+			map.insert("size".to_string(), size.to_json());
+		}
+		let json = Json::Object(map);
+		Number::list(&self.client, &path, json, self.number_pattern.clone())
+	}
+}
+
 #[derive(Debug)]
 struct Data{
 	name: Lazy<Option<String>>,
@@ -44,10 +248,31 @@ struct Data{
 	fallback_number: Lazy<Option<String>>
 }
 
+impl Data{
+	fn from_info(info: NumberInfo) -> CatapultResult<Data>{
+		Ok(Data{
+			name: Available(info.name),
+			number: Available(info.number),
+			national_number: Available(info.nationalNumber),
+			created_time: Available(info.createdTime),
+			city: Available(info.city),
+			state: Available(info.state),
+			price: Available(info.price),
+			number_state: Available(info.numberState),
+			application_id: Available(match info.application{
+				Some(url) => Some(try!(util::get_id_from_location_url(&url))),
+				None => None
+			}),
+			fallback_number: Available(info.fallbackNumber)
+		})
+	}
+}
+
 mod info{
 	#![allow(non_snake_case)]
 	#[derive(RustcDecodable)]
 	pub struct NumberInfo{
+		pub id: String,
 		pub name: Option<String>,
 		pub number: String,
 		pub nationalNumber: String,
@@ -60,6 +285,17 @@ mod info{
 		pub fallbackNumber: Option<String>
 	}
 	
+	#[derive(RustcDecodable, Debug)]
+	pub struct AvailableNumberInfo{
+		pub number: String,
+		pub nationalNumber: String,
+		pub city: Option<String>,
+		pub rateCenter: Option<String>,
+		pub lata: Option<String>,
+		pub state: Option<String>,
+		pub price: String
+	}
+	
 	#[derive(RustcDecodable, Debug)]
 	pub struct AllocatedNumber{
 		pub number: String,
@@ -70,24 +306,12 @@ mod info{
 }
 
 
-impl<'a> Number<'a>{
+impl Number{
 	fn load(&self) -> CatapultResult<()>{
 		let path = "users/".to_string() + &self.client.get_user_id() + "/phoneNumbers/" + &self.id;
 		let res:JsonResponse<NumberInfo> = try!(self.client.raw_get_request(&path, (), ()));
 		let mut data = self.data.lock().unwrap();
-		data.name = Available(res.body.name);
-		data.number = Available(res.body.number);
-		data.national_number = Available(res.body.nationalNumber);
-		data.created_time = Available(res.body.createdTime);
-		data.city = Available(res.body.city);
-		data.state = Available(res.body.state);
-		data.price = Available(res.body.price);
-		data.number_state = Available(res.body.numberState);
-		data.application_id = Available(match res.body.application{
-			Some(url) => Some(try!(util::get_id_from_location_url(&url))),
-			None => None
-		});
-		data.fallback_number = Available(res.body.fallbackNumber);
+		*data = try!(Data::from_info(res.body));
 		Ok(())
 	}
 	pub fn save(&self) -> CatapultResult<()>{
@@ -107,10 +331,10 @@ impl<'a> Number<'a>{
 		let _:EmptyResponse = try!(self.client.raw_post_request(&path, (), &json));
 		Ok(())
 	}
-	pub fn by_id(client: &'a Client, id: &str) -> Number<'a>{
+	pub fn by_id(client: &Client, id: &str) -> Number{
 		Number{
 			id: id.to_string(),
-			client: client,
+			client: client.clone(),
 			data: Arc::new(Mutex::new(Data{
 				created_time: NotLoaded,
 				fallback_number: NotLoaded,
@@ -126,38 +350,79 @@ impl<'a> Number<'a>{
 		}
 	}
 
-	pub fn search_and_allocate_local(client: &'a Client, quantity: u32, search: Search) -> CatapultResult<Vec<Number<'a>>>{
-		let json = match search{
-			Search::ByCity{city, state} => json!({
-				"city" => (city),
-				"state" => (state),
-				"quantity" => (quantity)
-			}),
-			Search::ByState(state) => json!({
-				"state" => (state),
-				"quantity" => (quantity)
-			}),
-			Search::ByZip(zip) => json!({
-				"zip" => (zip),
-				"quantity" => (quantity)
-			}),
-			Search::ByAreaCode{
-				area_code, local_number, in_local_calling_area
-			} => json!({
-				"areaCode" => (area_code),
-				"localNumber" => (local_number),
-				"inLocalCallingArea" => (in_local_calling_area),
-				"quantity" => (quantity)
-			}),
-		};
-		let res:JsonResponse<Vec<AllocatedNumber>> = try!(client.raw_post_request("availableNumbers/local", json, ()));
+	///Looks up one of your numbers, eg: `Number::by_number(&client, &"+19195551234".parse()?)`
+	pub fn by_number<N: Into<PhoneNumber>>(client: &Client, number: N) -> CatapultResult<Number>{
+		let path = "users/".to_string() + &client.get_user_id() + "/phoneNumbers/" + &number.into().get_e164();
+		let res:JsonResponse<NumberInfo> = try!(client.raw_get_request(&path, (), ()));
+		Ok(Number{
+			id: res.body.id.clone(),
+			client: client.clone(),
+			data: Arc::new(Mutex::new(try!(Data::from_info(res.body))))
+		})
+	}
+	pub fn query(client: &Client) -> Query{
+		Query{
+			client: client.clone(),
+			application_id: None,
+			state: None,
+			city: None,
+			name: None,
+			number_state: None,
+			number_pattern: None,
+			size: None
+		}
+	}
+	fn list<P: ToJson>(client: &Client, path: &str, params: P, number_pattern: Option<String>) -> CatapultResult<QueryResult>{
+		let res:JsonResponse<Vec<NumberInfo>> = try!(client.raw_get_request(&path, params, ()));
+		let mut output = vec!();
+		for info in res.body{
+			if let Some(ref pattern) = number_pattern{
+				if !util::matches_number_pattern(pattern, &info.number){
+					continue;
+				}
+			}
+			output.push(Number{
+				id: info.id.clone(),
+				client: client.clone(),
+				data: Arc::new(Mutex::new(try!(Data::from_info(info))))
+			});
+		}
+		let next_url = try!(util::get_next_link_from_headers(&res.headers));
+		Ok(QueryResult{
+			client: client.clone(),
+			data: output,
+			next_url: next_url,
+			number_pattern: number_pattern
+		})
+	}
+	///Allocates a number found with AvailableNumber::search_local() or search_toll_free()
+	pub fn order<N: Into<PhoneNumber>>(client: &Client, number: N) -> CatapultResult<Number>{
+		let number = number.into().get_e164();
+		let path = "users/".to_string() + &client.get_user_id() + "/phoneNumbers";
+		let json = json!({
+			"number" => (number)
+		});
+		let res:EmptyResponse = try!(client.raw_post_request(&path, (), &json));
+		let id = try!(util::get_id_from_location_header(&res.headers));
+		let output = Self::by_id(client, &id);
+		output.data.lock().unwrap().number = Available(number);
+		Ok(output)
+	}
+	pub fn search_and_allocate_local(client: &Client, quantity: u32, search: Search) -> CatapultResult<Vec<Number>>{
+		Self::search_and_allocate(client, "availableNumbers/local", search.to_json(quantity))
+	}
+	pub fn search_and_allocate_toll_free(client: &Client, quantity: u32, search: TollFreeSearch) -> CatapultResult<Vec<Number>>{
+		Self::search_and_allocate(client, "availableNumbers/tollFree", search.to_json(quantity))
+	}
+	fn search_and_allocate(client: &Client, path: &str, params: Json) -> CatapultResult<Vec<Number>>{
+		let res:JsonResponse<Vec<AllocatedNumber>> = try!(client.raw_post_request(path, params, ()));
 		
 		let mut output = vec!();
 		for number in res.body{
 			let id = try!(util::get_id_from_location_url(&number.location));
 			output.push(Number{
 				id: id.to_string(),
-				client: client,
+				client: client.clone(),
 				data: Arc::new(Mutex::new(Data{
 					created_time: NotLoaded,
 					fallback_number: NotLoaded,
@@ -217,6 +482,13 @@ impl<'a> Number<'a>{
 	pub fn set_application_id(&self, id: Option<&str>){
 		self.data.lock().unwrap().application_id = Available(id.map(|a|a.to_string()));
 	}
+	pub fn set_name(&self, name: Option<&str>){
+		self.data.lock().unwrap().name = Available(name.map(|a|a.to_string()));
+	}
+	///Pass `None::<PhoneNumber>` to remove the fallback number
+	pub fn set_fallback_number<N: Into<PhoneNumber>>(&self, number: Option<N>){
+		self.data.lock().unwrap().fallback_number = Available(number.map(|a|a.into().get_e164()));
+	}
 	
 	/* Actions */
 	pub fn release(&self) -> CatapultResult<()>{
@@ -0,0 +1,88 @@
+use {CatapultResult, Client, PhoneNumber};
+use client::JsonResponse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use self::info::CnamInfo;
+
+mod info{
+	#![allow(non_snake_case)]
+	#[derive(RustcDecodable)]
+	pub struct CnamInfo{
+		pub name: Option<String>,
+		pub number: String,
+		pub created: String,
+		pub updated: String
+	}
+}
+
+///Caller name (CNAM) information for any phone number
+#[derive(Clone, Debug)]
+pub struct NumberInfo{
+	number: String,
+	name: Option<String>,
+	created: String,
+	updated: String
+}
+impl NumberInfo{
+	pub fn get<N: Into<PhoneNumber>>(client: &Client, number: N) -> CatapultResult<NumberInfo>{
+		let path = "phoneNumbers/numberInfo/".to_string() + &number.into().get_e164();
+		let res:JsonResponse<CnamInfo> = try!(client.raw_get_request(&path, (), ()));
+		Ok(NumberInfo{
+			number: res.body.number,
+			name: res.body.name,
+			created: res.body.created,
+			updated: res.body.updated
+		})
+	}
+
+	/* Getters */
+	pub fn get_number(&self) -> String{
+		self.number.clone()
+	}
+	///The caller name, if one is registered for this number
+	pub fn get_name(&self) -> Option<String>{
+		self.name.clone()
+	}
+	pub fn get_created_time(&self) -> String{
+		self.created.clone()
+	}
+	pub fn get_updated_time(&self) -> String{
+		self.updated.clone()
+	}
+}
+
+///Remembers NumberInfo lookups for `ttl`, since caller names rarely change
+#[derive(Clone)]
+pub struct NumberInfoCache{
+	client: Client,
+	ttl: Duration,
+	entries: Arc<Mutex<HashMap<String, (Instant, NumberInfo)>>>
+}
+impl NumberInfoCache{
+	pub fn new(client: &Client, ttl: Duration) -> NumberInfoCache{
+		NumberInfoCache{
+			client: client.clone(),
+			ttl: ttl,
+			entries: Arc::new(Mutex::new(HashMap::new()))
+		}
+	}
+	pub fn get<N: Into<PhoneNumber>>(&self, number: N) -> CatapultResult<NumberInfo>{
+		let number = number.into();
+		if let Some(&(fetched, ref info)) = self.entries.lock().unwrap().get(number.as_ref()){
+			if fetched.elapsed() < self.ttl{
+				return Ok(info.clone())
+			}
+		}
+		let info = try!(NumberInfo::get(&self.client, &number));
+		let mut entries = self.entries.lock().unwrap();
+		//drop expired entries so the cache doesn't grow forever
+		let ttl = self.ttl;
+		entries.retain(|_, &mut (fetched, _)| fetched.elapsed() < ttl);
+		entries.insert(number.get_e164(), (Instant::now(), info.clone()));
+		Ok(info)
+	}
+	pub fn clear(&self){
+		self.entries.lock().unwrap().clear();
+	}
+}
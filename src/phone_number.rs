@@ -0,0 +1,142 @@
+use {CatapultResult, CatapultError};
+use std::fmt;
+use std::str::FromStr;
+
+///A validated phone number, stored in E.164 form (eg: +19195551234).
+///APIs that take a number accept `PhoneNumber` or `&PhoneNumber`, so malformed numbers
+///are caught by parse() instead of by the server
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PhoneNumber{
+	e164: String
+}
+impl PhoneNumber{
+	///Accepts E.164 (+19195551234) and common North American formats,
+	///such as 9195551234, 1-919-555-1234 and (919) 555-1234.
+	///Numbers without a leading + are assumed to be North American
+	pub fn parse(input: &str) -> CatapultResult<PhoneNumber>{
+		let invalid = || CatapultError::bad_input(&format!("invalid phone number: {}", input));
+		let trimmed = input.trim();
+		let mut digits = String::new();
+		let mut international = false;
+		for (i, c) in trimmed.chars().enumerate(){
+			match c{
+				'+' if i == 0 => international = true,
+				' ' | '-' | '.' | '(' | ')' => {},
+				c if c.is_digit(10) => digits.push(c),
+				_ => return Err(invalid())
+			}
+		}
+		let digits = if international{
+			digits
+		}else{
+			match digits.len(){
+				10 => "1".to_string() + &digits,
+				11 if digits.starts_with('1') => digits,
+				_ => return Err(invalid())
+			}
+		};
+		if digits.starts_with('1'){
+			//North American numbers: 1 + 3 digit area code + 7 digit number.
+			//Neither the area code nor the exchange can start with 0 or 1
+			let bytes = digits.as_bytes();
+			if bytes.len() != 11 || bytes[1] < b'2' || bytes[4] < b'2'{
+				return Err(invalid())
+			}
+		}else if digits.len() < 8 || digits.len() > 15 || digits.starts_with('0'){
+			return Err(invalid())
+		}
+		Ok(PhoneNumber{
+			e164: "+".to_string() + &digits
+		})
+	}
+
+	/* Getters */
+	///eg: +19195551234
+	pub fn get_e164(&self) -> String{
+		self.e164.clone()
+	}
+	///True for +1 (US, Canada and the rest of the North American Numbering Plan)
+	pub fn is_north_american(&self) -> bool{
+		self.e164.starts_with("+1")
+	}
+	///eg: (919) 555-1234. Numbers outside North America are left in E.164 form
+	pub fn format_national(&self) -> String{
+		if !self.is_north_american(){
+			return self.e164.clone()
+		}
+		let n = &self.e164[2..];
+		format!("({}) {}-{}", &n[0..3], &n[3..6], &n[6..])
+	}
+	///eg: +1 919-555-1234. Numbers outside North America are left in E.164 form
+	pub fn format_international(&self) -> String{
+		if !self.is_north_american(){
+			return self.e164.clone()
+		}
+		let n = &self.e164[2..];
+		format!("+1 {}-{}-{}", &n[0..3], &n[3..6], &n[6..])
+	}
+}
+impl FromStr for PhoneNumber{
+	type Err = CatapultError;
+	fn from_str(s: &str) -> CatapultResult<PhoneNumber>{
+		PhoneNumber::parse(s)
+	}
+}
+impl<'a> From<&'a PhoneNumber> for PhoneNumber{
+	fn from(number: &'a PhoneNumber) -> PhoneNumber{
+		number.clone()
+	}
+}
+impl AsRef<str> for PhoneNumber{
+	fn as_ref(&self) -> &str{
+		&self.e164
+	}
+}
+impl From<PhoneNumber> for String{
+	fn from(number: PhoneNumber) -> String{
+		number.e164
+	}
+}
+impl fmt::Display for PhoneNumber{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+		write!(f, "{}", self.e164)
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use super::PhoneNumber;
+
+	#[test]
+	fn parses_north_american_formats(){
+		for input in &["+19195551234", "19195551234", "9195551234", "1-919-555-1234", "(919) 555-1234", " 919.555.1234 "]{
+			assert_eq!(PhoneNumber::parse(input).unwrap().get_e164(), "+19195551234");
+		}
+	}
+	#[test]
+	fn parses_international_numbers(){
+		let number = PhoneNumber::parse("+44 20 7946 0958").unwrap();
+		assert_eq!(number.get_e164(), "+442079460958");
+		assert!(!number.is_north_american());
+	}
+	#[test]
+	fn rejects_invalid_numbers(){
+		for input in &["", "555-1234", "919555123", "29195551234", "+1919555123", "(019) 555-1234",
+			"(919) 155-1234", "919-555-123a", "+0123456789", "+1234567", "+1234567890123456", "1+9195551234"]{
+			assert!(PhoneNumber::parse(input).is_err(), "accepted {}", input);
+		}
+	}
+	#[test]
+	fn formats_north_american_numbers(){
+		let number = PhoneNumber::parse("9195551234").unwrap();
+		assert_eq!(number.format_national(), "(919) 555-1234");
+		assert_eq!(number.format_international(), "+1 919-555-1234");
+		assert_eq!(number.to_string(), "+19195551234");
+	}
+	#[test]
+	fn leaves_other_numbers_in_e164(){
+		let number = PhoneNumber::parse("+442079460958").unwrap();
+		assert_eq!(number.format_national(), "+442079460958");
+		assert_eq!(number.format_international(), "+442079460958");
+	}
+}
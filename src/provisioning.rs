@@ -0,0 +1,107 @@
+use {CatapultResult, CatapultError, Client};
+use application::Application;
+use number::{Number, Search};
+
+///Allocates numbers and assigns them to an application.
+///If any step fails, every number that was allocated is released again
+pub struct ProvisioningBuilder{
+	client: Client,
+	application_id: String,
+	quantity: u32,
+	search: Search,
+	name: Option<String>
+}
+impl ProvisioningBuilder{
+	pub fn new(client: &Client, app: &Application, quantity: u32, search: Search) -> ProvisioningBuilder{
+		ProvisioningBuilder{
+			client: client.clone(),
+			application_id: app.get_id(),
+			quantity: quantity,
+			search: search,
+			name: None
+		}
+	}
+	///Name given to every provisioned number
+	pub fn name(mut self, name: &str) -> Self{
+		self.name = Some(name.to_owned()); self
+	}
+	pub fn create(self) -> ProvisioningReport{
+		let mut report = ProvisioningReport{
+			numbers: vec!(),
+			released: vec!(),
+			release_failures: vec!(),
+			error: None
+		};
+		let numbers = match Number::search_and_allocate_local(&self.client, self.quantity, self.search){
+			Ok(numbers) => numbers,
+			Err(err) => {
+				report.error = Some(err);
+				return report
+			}
+		};
+		let mut error = None;
+		if numbers.len() < self.quantity as usize{
+			error = Some(CatapultError::unexpected(&format!(
+				"only {} of {} numbers could be allocated", numbers.len(), self.quantity
+			)));
+		}else{
+			for number in numbers.iter(){
+				number.set_application_id(Some(&self.application_id));
+				if let Some(ref name) = self.name{
+					number.set_name(Some(name));
+				}
+				if let Err(err) = number.save(){
+					error = Some(err);
+					break;
+				}
+			}
+		}
+		match error{
+			None => report.numbers = numbers,
+			Some(err) => {
+				report.error = Some(err);
+				for number in numbers{
+					match number.release(){
+						Ok(()) => report.released.push(number),
+						Err(err) => report.release_failures.push((number, err))
+					}
+				}
+			}
+		}
+		report
+	}
+}
+
+pub struct ProvisioningReport{
+	numbers: Vec<Number>,
+	released: Vec<Number>,
+	release_failures: Vec<(Number, CatapultError)>,
+	error: Option<CatapultError>
+}
+impl ProvisioningReport{
+	pub fn is_success(&self) -> bool{
+		self.error.is_none()
+	}
+	///Why provisioning failed and was rolled back
+	pub fn get_error(&self) -> Option<&CatapultError>{
+		self.error.as_ref()
+	}
+	///Numbers that were allocated and assigned. Empty if provisioning failed
+	pub fn get_numbers(&self) -> &Vec<Number>{
+		&self.numbers
+	}
+	///Numbers released while rolling back
+	pub fn get_released(&self) -> &Vec<Number>{
+		&self.released
+	}
+	///Numbers that could not be released while rolling back. These are still in your account
+	pub fn get_release_failures(&self) -> &Vec<(Number, CatapultError)>{
+		&self.release_failures
+	}
+	pub fn into_result(self) -> CatapultResult<Vec<Number>>{
+		match self.error{
+			Some(err) => Err(err),
+			None => Ok(self.numbers)
+		}
+	}
+}
@@ -0,0 +1,285 @@
+use {CatapultResult, CatapultError, Client};
+use message::{Message, PendingMessage, SavedMessage};
+use rustc_serialize::json;
+use util;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn new_id() -> String{
+	format!("sm-{}", util::generate_id())
+}
+fn to_unix_secs(time: SystemTime) -> u64{
+	match time.duration_since(UNIX_EPOCH){
+		Ok(duration) => duration.as_secs(),
+		Err(_) => 0
+	}
+}
+
+#[derive(RustcEncodable, RustcDecodable, Clone, Debug)]
+pub struct ScheduledMessage{
+	id: String,
+	send_at: u64,
+	message: SavedMessage
+}
+impl ScheduledMessage{
+	pub fn get_id(&self) -> String{
+		self.id.clone()
+	}
+	pub fn get_send_at(&self) -> SystemTime{
+		UNIX_EPOCH + Duration::from_secs(self.send_at)
+	}
+	pub fn get_message(&self) -> SavedMessage{
+		self.message.clone()
+	}
+}
+
+///Storage for messages waiting to be sent.
+///Implement this to keep scheduled messages somewhere other than memory or a file
+pub trait ScheduleStore: Send + Sync{
+	///Adds the message, replacing any message with the same id
+	fn save(&self, msg: ScheduledMessage) -> CatapultResult<()>;
+	fn remove(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>;
+	///Changes the send time of a message. Returns false if there is no message with that id
+	fn reschedule(&self, id: &str, send_at: u64) -> CatapultResult<bool>;
+	fn get(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>;
+	fn list(&self) -> CatapultResult<Vec<ScheduledMessage>>;
+	///Removes and returns every message due at or before `now` (seconds since the unix epoch)
+	fn take_due(&self, now: u64) -> CatapultResult<Vec<ScheduledMessage>>;
+}
+
+fn reschedule_in_map(map: &mut BTreeMap<String, ScheduledMessage>, id: &str, send_at: u64) -> bool{
+	match map.get_mut(id){
+		Some(msg) => {
+			msg.send_at = send_at;
+			true
+		},
+		None => false
+	}
+}
+fn take_due_from_map(map: &mut BTreeMap<String, ScheduledMessage>, now: u64) -> Vec<ScheduledMessage>{
+	let due: Vec<String> = map.values()
+		.filter(|msg| msg.send_at <= now)
+		.map(|msg| msg.id.clone())
+		.collect();
+	let mut output: Vec<ScheduledMessage> = due.iter().filter_map(|id| map.remove(id)).collect();
+	output.sort_by(|a, b| a.send_at.cmp(&b.send_at));
+	output
+}
+
+///Scheduled messages are lost when the process exits
+pub struct MemoryScheduleStore{
+	messages: Mutex<BTreeMap<String, ScheduledMessage>>
+}
+impl MemoryScheduleStore{
+	pub fn new() -> MemoryScheduleStore{
+		MemoryScheduleStore{
+			messages: Mutex::new(BTreeMap::new())
+		}
+	}
+}
+impl ScheduleStore for MemoryScheduleStore{
+	fn save(&self, msg: ScheduledMessage) -> CatapultResult<()>{
+		self.messages.lock().unwrap().insert(msg.id.clone(), msg);
+		Ok(())
+	}
+	fn remove(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>{
+		Ok(self.messages.lock().unwrap().remove(id))
+	}
+	fn reschedule(&self, id: &str, send_at: u64) -> CatapultResult<bool>{
+		Ok(reschedule_in_map(&mut self.messages.lock().unwrap(), id, send_at))
+	}
+	fn get(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>{
+		Ok(self.messages.lock().unwrap().get(id).cloned())
+	}
+	fn list(&self) -> CatapultResult<Vec<ScheduledMessage>>{
+		Ok(self.messages.lock().unwrap().values().cloned().collect())
+	}
+	fn take_due(&self, now: u64) -> CatapultResult<Vec<ScheduledMessage>>{
+		Ok(take_due_from_map(&mut self.messages.lock().unwrap(), now))
+	}
+}
+
+///Keeps scheduled messages in a JSON file so they survive a restart.
+///Only one process should use a file at a time
+pub struct FileScheduleStore{
+	path: PathBuf,
+	lock: Mutex<()>
+}
+impl FileScheduleStore{
+	///The file is created when the first message is scheduled
+	pub fn new<P: AsRef<Path>>(path: P) -> FileScheduleStore{
+		FileScheduleStore{
+			path: path.as_ref().to_owned(),
+			lock: Mutex::new(())
+		}
+	}
+	fn read(&self) -> CatapultResult<BTreeMap<String, ScheduledMessage>>{
+		let mut data = String::new();
+		match File::open(&self.path){
+			Ok(mut file) => try!(file.read_to_string(&mut data)),
+			Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+			Err(err) => return Err(CatapultError::IoError(err))
+		};
+		let list: Vec<ScheduledMessage> = try!(json::decode(&data));
+		Ok(list.into_iter().map(|msg| (msg.id.clone(), msg)).collect())
+	}
+	///Writes to a temporary file first so a crash can't leave a half written file
+	fn write(&self, map: &BTreeMap<String, ScheduledMessage>) -> CatapultResult<()>{
+		let list: Vec<&ScheduledMessage> = map.values().collect();
+		let data = try!(json::encode(&list));
+		let mut tmp_path = self.path.clone().into_os_string();
+		tmp_path.push(".tmp");
+		{
+			let mut file = try!(File::create(&tmp_path));
+			try!(file.write_all(data.as_bytes()));
+			try!(file.sync_all());
+		}
+		try!(fs::rename(&tmp_path, &self.path));
+		Ok(())
+	}
+	fn update<T, F>(&self, f: F) -> CatapultResult<T>
+	where F: FnOnce(&mut BTreeMap<String, ScheduledMessage>) -> T{
+		let _lock = self.lock.lock().unwrap();
+		let mut map = try!(self.read());
+		let output = f(&mut map);
+		try!(self.write(&map));
+		Ok(output)
+	}
+}
+impl ScheduleStore for FileScheduleStore{
+	fn save(&self, msg: ScheduledMessage) -> CatapultResult<()>{
+		self.update(|map|{
+			map.insert(msg.id.clone(), msg);
+		})
+	}
+	fn remove(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>{
+		self.update(|map| map.remove(id))
+	}
+	fn reschedule(&self, id: &str, send_at: u64) -> CatapultResult<bool>{
+		self.update(|map| reschedule_in_map(map, id, send_at))
+	}
+	fn get(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>{
+		let _lock = self.lock.lock().unwrap();
+		Ok(try!(self.read()).remove(id))
+	}
+	fn list(&self) -> CatapultResult<Vec<ScheduledMessage>>{
+		let _lock = self.lock.lock().unwrap();
+		Ok(try!(self.read()).into_iter().map(|(_, msg)| msg).collect())
+	}
+	fn take_due(&self, now: u64) -> CatapultResult<Vec<ScheduledMessage>>{
+		self.update(|map| take_due_from_map(map, now))
+	}
+}
+
+///Holds messages until their send time, then sends them with PendingMessage::create().
+///Messages are removed from the store before they are sent, so a crash while sending can drop a message
+///but never sends one twice
+pub struct Scheduler{
+	client: Client,
+	store: Arc<ScheduleStore>,
+	running: Arc<AtomicBool>,
+	worker: Mutex<Option<JoinHandle<()>>>
+}
+impl Scheduler{
+	pub fn new(client: &Client, store: Arc<ScheduleStore>) -> Scheduler{
+		Scheduler{
+			client: client.clone(),
+			store: store,
+			running: Arc::new(AtomicBool::new(false)),
+			worker: Mutex::new(None)
+		}
+	}
+	///Returns the id of the scheduled message
+	pub fn schedule(&self, msg: PendingMessage, send_at: SystemTime) -> CatapultResult<String>{
+		let id = new_id();
+		try!(self.store.save(ScheduledMessage{
+			id: id.clone(),
+			send_at: to_unix_secs(send_at),
+			message: try!(msg.to_saved())
+		}));
+		Ok(id)
+	}
+	///Returns false if the message was already sent or cancelled
+	pub fn cancel(&self, id: &str) -> CatapultResult<bool>{
+		Ok(try!(self.store.remove(id)).is_some())
+	}
+	///Returns false if the message was already sent or cancelled
+	pub fn reschedule(&self, id: &str, send_at: SystemTime) -> CatapultResult<bool>{
+		self.store.reschedule(id, to_unix_secs(send_at))
+	}
+	pub fn get_scheduled(&self, id: &str) -> CatapultResult<Option<ScheduledMessage>>{
+		self.store.get(id)
+	}
+	pub fn list_scheduled(&self) -> CatapultResult<Vec<ScheduledMessage>>{
+		self.store.list()
+	}
+	///Sends every message that is due now. Returns the result of each send
+	pub fn send_due(&self) -> CatapultResult<Vec<(ScheduledMessage, CatapultResult<Message>)>>{
+		send_due(&self.client, &*self.store)
+	}
+
+	///Starts a thread that sends due messages, checking every `poll_interval`.
+	///`on_sent` is called with the result of each send.
+	///`on_error` is called when the store can't be read, which stops any message from being sent.
+	///The worker keeps polling in case the store recovers
+	pub fn start<F, E>(&self, poll_interval: Duration, on_sent: F, on_error: E) -> CatapultResult<()>
+	where F: Fn(ScheduledMessage, CatapultResult<Message>) + Send + 'static,
+	E: Fn(CatapultError) + Send + 'static{
+		let mut worker = self.worker.lock().unwrap();
+		if worker.is_some(){
+			return Err(CatapultError::bad_input("scheduler is already running"))
+		}
+		self.running.store(true, Ordering::SeqCst);
+		let client = self.client.clone();
+		let store = self.store.clone();
+		let running = self.running.clone();
+		*worker = Some(thread::spawn(move ||{
+			while running.load(Ordering::SeqCst){
+				match send_due(&client, &*store){
+					Ok(results) => {
+						for (msg, result) in results{
+							on_sent(msg, result);
+						}
+					},
+					Err(err) => on_error(err)
+				}
+				thread::sleep(poll_interval);
+			}
+		}));
+		Ok(())
+	}
+	///Stops the worker thread, waiting for it to finish sending
+	pub fn stop(&self){
+		self.running.store(false, Ordering::SeqCst);
+		if let Some(worker) = self.worker.lock().unwrap().take(){
+			let _ = worker.join();
+		}
+	}
+
+	/* Getters */
+	pub fn get_client(&self) -> Client{
+		self.client.clone()
+	}
+	pub fn get_store(&self) -> Arc<ScheduleStore>{
+		self.store.clone()
+	}
+}
+impl Drop for Scheduler{
+	fn drop(&mut self){
+		self.stop();
+	}
+}
+
+fn send_due(client: &Client, store: &ScheduleStore) -> CatapultResult<Vec<(ScheduledMessage, CatapultResult<Message>)>>{
+	let due = try!(store.take_due(to_unix_secs(SystemTime::now())));
+	Ok(due.into_iter().map(|msg|{
+		let result = PendingMessage::from_saved(client, &msg.message).create();
+		(msg, result)
+	}).collect())
+}
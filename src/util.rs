@@ -1,8 +1,19 @@
 use hyper::header;
 use std::path::Path;
 use {CatapultError, CatapultResult};
-use rustc_serialize::json::Json;
+use rustc_serialize::json::{self, Json};
+use rustc_serialize::Decodable;
 use hyper::Url;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+///Unique within this process, and unlikely to repeat across processes
+pub fn generate_id() -> String{
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
+	format!("{}{:09}-{}", now.as_secs(), now.subsec_nanos(), NEXT_ID.fetch_add(1, Ordering::SeqCst))
+}
 
 pub fn get_id_from_location_header(headers: &header::Headers) -> CatapultResult<String>{
 	match headers.get::<header::Location>(){
@@ -57,4 +68,38 @@ pub fn expect<T>(data: Option<T>, name: &str) -> CatapultResult<T>{
 		None => Err(CatapultError::unexpected(&format!("required field not found: {}", name)))
 	}
 }
-
+pub fn parse_json(data: &str) -> CatapultResult<Json>{
+	match Json::from_str(data){
+		Ok(json) => Ok(json),
+		Err(err) => Err(CatapultError::DecoderError(json::DecoderError::ParseError(err)))
+	}
+}
+pub fn decode_json<T: Decodable>(json: Json) -> CatapultResult<T>{
+	Ok(try!(Decodable::decode(&mut json::Decoder::new(json))))
+}
+///Some fields hold either a single string or a list of them. This wraps a single string in a list
+pub fn string_to_array(json: &mut Json, key: &str){
+	if let Some(obj) = json.as_object_mut(){
+		let value = obj.get(key).and_then(|value| value.as_string()).map(|value| value.to_owned());
+		if let Some(value) = value{
+			obj.insert(key.to_owned(), Json::Array(vec!(Json::String(value))));
+		}
+	}
+}
+///`?` matches any single digit and `*` matches any number of digits.
+///The pattern is matched against the number without its +1 country code
+pub fn matches_number_pattern(pattern: &str, number: &str) -> bool{
+	fn matches(pattern: &[u8], digits: &[u8]) -> bool{
+		match pattern.split_first(){
+			None => digits.is_empty(),
+			Some((&b'*', rest)) => (0..digits.len() + 1).any(|i| matches(rest, &digits[i..])),
+			Some((&b'?', rest)) => !digits.is_empty() && matches(rest, &digits[1..]),
+			Some((c, rest)) => digits.first() == Some(c) && matches(rest, &digits[1..])
+		}
+	}
+	let mut digits: Vec<u8> = number.bytes().filter(|c| c.is_ascii_digit()).collect();
+	if digits.len() == 11 && digits[0] == b'1'{
+		digits.remove(0);
+	}
+	matches(pattern.as_bytes(), &digits)
+}